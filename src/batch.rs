@@ -0,0 +1,232 @@
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Debug)]
+pub struct BatchError {
+    pub message: String,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+fn batch_error(message: impl Into<String>) -> BatchError {
+    BatchError {
+        message: message.into(),
+    }
+}
+
+/// An input file of a batch and the file it is rendered to
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+fn is_glob(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// The directory of a pattern before its first wildcard, or the directory of the file when it
+/// has no wildcards
+fn pattern_base(pattern: &str) -> PathBuf {
+    let path = Path::new(pattern);
+    if !is_glob(pattern) {
+        return path.parent().map(Path::to_path_buf).unwrap_or_default();
+    }
+
+    path.components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// The output path of an input matched by a pattern, which keeps the input's path relative to
+/// the pattern's base directory so that inputs with the same name in different directories
+/// don't overwrite each other
+pub fn output_path(pattern: &str, input: &Path, out_dir: &Path, extension: &str) -> PathBuf {
+    let relative = input
+        .strip_prefix(pattern_base(pattern))
+        .ok()
+        .filter(|relative| {
+            relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        })
+        .unwrap_or_else(|| Path::new(input.file_name().unwrap_or_default()));
+    out_dir.join(relative).with_extension(extension)
+}
+
+/// Expand the input files and glob patterns into the files to render. Every pattern has to
+/// match at least one file and no two inputs can be rendered to the same output.
+pub fn batch_items(
+    patterns: &[String],
+    out_dir: &Path,
+    extension: &str,
+) -> Result<Vec<BatchItem>, BatchError> {
+    let mut items: Vec<BatchItem> = Vec::new();
+    for pattern in patterns {
+        let paths = glob::glob(pattern)
+            .map_err(|e| batch_error(format!("Invalid pattern '{}': {}", pattern, e)))?;
+
+        let mut matched = false;
+        for path in paths {
+            let input =
+                path.map_err(|e| batch_error(format!("Failed to read '{}': {}", pattern, e)))?;
+            if !input.is_file() {
+                continue;
+            }
+            matched = true;
+            if items.iter().any(|item| item.input == input) {
+                continue;
+            }
+
+            let output = output_path(pattern, &input, out_dir, extension);
+            if let Some(item) = items.iter().find(|item| item.output == output) {
+                return Err(batch_error(format!(
+                    "'{}' and '{}' would both be rendered to '{}'",
+                    item.input.display(),
+                    input.display(),
+                    output.display()
+                )));
+            }
+            items.push(BatchItem { input, output });
+        }
+
+        if !matched {
+            return Err(batch_error(format!(
+                "Pattern '{}' did not match any files",
+                pattern
+            )));
+        }
+    }
+    Ok(items)
+}
+
+/// Call the function for every item on up to `jobs` threads, returning the results in the order
+/// of the items
+pub fn map_parallel<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_output_path() {
+        let out_dir = Path::new("build");
+        assert_eq!(
+            output_path(
+                "reports/**/*.mmd",
+                Path::new("reports/q1/velocity.mmd"),
+                out_dir,
+                "png"
+            ),
+            Path::new("build/q1/velocity.png")
+        );
+        assert_eq!(
+            output_path("*.mmd", Path::new("velocity.mmd"), out_dir, "svg"),
+            Path::new("build/velocity.svg")
+        );
+        assert_eq!(
+            output_path(
+                "../reports/velocity.json",
+                Path::new("../reports/velocity.json"),
+                out_dir,
+                "png"
+            ),
+            Path::new("build/velocity.png")
+        );
+        assert_eq!(
+            output_path(
+                "reports/sprint-[0-9]/*.mmd",
+                Path::new("reports/sprint-1/burn-up.mmd"),
+                out_dir,
+                "png"
+            ),
+            Path::new("build/sprint-1/burn-up.png")
+        );
+    }
+
+    #[test]
+    fn test_batch_items() {
+        let dir = std::env::temp_dir().join(format!("pisnge-batch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        for file in ["a/chart.mmd", "b/chart.mmd", "b/other.mmd"] {
+            fs::write(dir.join(file), "pie\n").unwrap();
+        }
+        let pattern = |pattern: &str| dir.join(pattern).to_string_lossy().into_owned();
+        let out_dir = Path::new("out");
+
+        let items = batch_items(&[pattern("**/*.mmd")], out_dir, "png").unwrap();
+        let outputs: Vec<&Path> = items.iter().map(|item| item.output.as_path()).collect();
+        assert_eq!(
+            outputs,
+            vec![
+                Path::new("out/a/chart.png"),
+                Path::new("out/b/chart.png"),
+                Path::new("out/b/other.png")
+            ]
+        );
+
+        // Files matched by more than one pattern are only rendered once
+        let items = batch_items(
+            &[pattern("b/*.mmd"), pattern("b/other.mmd")],
+            out_dir,
+            "svg",
+        )
+        .unwrap();
+        assert_eq!(items.len(), 2);
+
+        assert!(batch_items(&[pattern("*/chart.mmd")], out_dir, "png").is_ok());
+        assert!(batch_items(&[pattern("a/*.mmd"), pattern("b/*.mmd")], out_dir, "png").is_err());
+        assert!(batch_items(&[pattern("*.json")], out_dir, "png").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_map_parallel() {
+        let items: Vec<u32> = (0..50).collect();
+        assert_eq!(
+            map_parallel(&items, 4, |item| item * 2),
+            (0..50).map(|item| item * 2).collect::<Vec<_>>()
+        );
+        assert!(map_parallel(&Vec::<u32>::new(), 4, |item| *item).is_empty());
+    }
+}
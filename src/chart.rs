@@ -0,0 +1,252 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use svg::Document;
+
+use crate::common::parser::{parse_config_and_detect_type, ChartType};
+use crate::common::ChartConfig;
+use crate::pie_chart::{parse_pie_chart_content, render_pie_chart_svg, PieChart};
+use crate::work_item_movement::{
+    parse_work_item_movement, render_work_item_movement_svg, validate_work_item_movement,
+    WorkItemMovement,
+};
+use crate::xychart::{parse_xychart_content, render_xychart_svg, XYChart};
+
+/// Any of the supported charts. In JSON the chart's fields are alongside a `type` of `pie`,
+/// `xy` or `work-item-movement`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "kebab-case"))]
+pub enum Chart {
+    Pie(PieChart),
+    #[cfg_attr(feature = "serde", serde(rename = "xy"))]
+    XY(XYChart),
+    WorkItemMovement(WorkItemMovement),
+}
+
+#[derive(Debug)]
+pub struct ChartError {
+    pub message: String,
+}
+
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+fn chart_error(message: impl Into<String>) -> ChartError {
+    ChartError {
+        message: message.into(),
+    }
+}
+
+impl Chart {
+    /// Parse a chart of any type from mermaid syntax, work item movement charts are validated
+    pub fn parse(input: &str) -> Result<Self, ChartError> {
+        // Ensure content ends with newline for easier parsing
+        let normalized_content = if input.ends_with('\n') {
+            input.to_string()
+        } else {
+            format!("{}\n", input)
+        };
+
+        let (_, (config, chart_type, content)) = parse_config_and_detect_type(&normalized_content)
+            .map_err(|e| {
+                chart_error(format!(
+                    "Failed to parse chart (unknown type or invalid config): {:?}",
+                    e
+                ))
+            })?;
+
+        let chart = match chart_type {
+            ChartType::Pie => parse_pie_chart_content(content, config)
+                .map(|(_, pie_chart)| Chart::Pie(pie_chart))
+                .map_err(|e| chart_error(format!("Failed to parse pie chart: {:?}", e)))?,
+            ChartType::XY => parse_xychart_content(content, config)
+                .map(|(_, xychart)| Chart::XY(xychart))
+                .map_err(|e| chart_error(format!("Failed to parse XY chart: {:?}", e)))?,
+            ChartType::WorkItemMovement => parse_work_item_movement(content, config)
+                .map(|(_, chart)| Chart::WorkItemMovement(chart))
+                .map_err(|e| {
+                    chart_error(format!("Failed to parse work item movement chart: {:?}", e))
+                })?,
+        };
+        chart.validate()?;
+        Ok(chart)
+    }
+
+    /// Read a chart of any type from JSON, work item movement charts are validated
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, ChartError> {
+        let chart: Chart = serde_json::from_str(json)
+            .map_err(|e| chart_error(format!("Invalid chart JSON: {}", e)))?;
+        chart.validate()?;
+        Ok(chart)
+    }
+
+    /// Read a chart from a file of mermaid syntax, or from a JSON description for `.json` files
+    pub fn from_file(path: &Path) -> Result<Self, ChartError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| chart_error(format!("Failed to read input file: {}", e)))?;
+        let is_json = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if !is_json {
+            return Self::parse(&content);
+        }
+
+        #[cfg(feature = "serde")]
+        return Self::from_json(&content);
+        #[cfg(not(feature = "serde"))]
+        Err(chart_error(
+            "Reading charts from JSON requires the serde feature",
+        ))
+    }
+
+    fn validate(&self) -> Result<(), ChartError> {
+        match self {
+            Chart::WorkItemMovement(chart) => {
+                validate_work_item_movement(chart).map_err(|e| chart_error(e.message))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn chart_type(&self) -> ChartType {
+        match self {
+            Chart::Pie(_) => ChartType::Pie,
+            Chart::XY(_) => ChartType::XY,
+            Chart::WorkItemMovement(_) => ChartType::WorkItemMovement,
+        }
+    }
+
+    pub fn config(&self) -> Option<&ChartConfig> {
+        match self {
+            Chart::Pie(pie_chart) => pie_chart.config.as_ref(),
+            Chart::XY(xychart) => xychart.config.as_ref(),
+            Chart::WorkItemMovement(chart) => chart.config.as_ref(),
+        }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Chart::Pie(pie_chart) => pie_chart.title.as_deref(),
+            Chart::XY(xychart) => xychart.title.as_deref(),
+            Chart::WorkItemMovement(chart) => chart.title.as_deref(),
+        }
+    }
+
+    /// Render the chart with the renderer for its type, returning the document along with its
+    /// actual width and height
+    pub fn render_svg(&self, width: u32, height: u32, font_name: &str) -> (Document, u32, u32) {
+        match self {
+            Chart::Pie(pie_chart) => render_pie_chart_svg(pie_chart, width, height, font_name),
+            Chart::XY(xychart) => render_xychart_svg(xychart, width, height, font_name),
+            Chart::WorkItemMovement(chart) => {
+                render_work_item_movement_svg(chart, width, height, font_name)
+            }
+        }
+    }
+
+    /// The chart in mermaid syntax, preceded by the `%%{init}%%` line of any config
+    pub fn to_mermaid(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Chart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Chart::Pie(pie_chart) => pie_chart.fmt(f),
+            Chart::XY(xychart) => xychart.fmt(f),
+            Chart::WorkItemMovement(chart) => chart.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chart() {
+        let chart = Chart::parse("pie title Points\n  \"Done\": 3").unwrap();
+        assert_eq!(chart.chart_type(), ChartType::Pie);
+
+        let chart = Chart::parse(
+            "%%{init: {'theme': 'base'}}%%\nxychart-beta\n  x-axis [A]\n  y-axis \"Days\" 0 --> 5\n  bar [1]\n",
+        )
+        .unwrap();
+        assert_eq!(chart.chart_type(), ChartType::XY);
+        assert_eq!(chart.config().unwrap().theme, "base");
+
+        let error =
+            Chart::parse("work-item-movement\n  columns [To Do]\n  PJ-1 To Do: 1 -> Done: 1\n")
+                .unwrap_err();
+        assert!(error.message.contains("'Done'"));
+        assert!(Chart::parse("flowchart LR\n  A --> B\n").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chart_from_json() {
+        let chart = Chart::from_json(
+            r##"{
+  "type": "xy",
+  "config": {"themeVariables": {"xyChart.plotColorPalette": "#ff8b00"}},
+  "title": "Story points",
+  "xAxis": {"labels": ["Sprint 1", "Sprint 2"]},
+  "yAxis": {"title": "Points", "min": 0, "max": 60},
+  "series": [
+    {"type": "bar", "name": "Done", "data": [40, null]},
+    {"type": "stacked-area", "axis": "right", "data": [1, 2]}
+  ],
+  "annotations": [
+    {"type": "horizontalLine", "value": 50, "label": "Target"},
+    {"type": "band", "from": 10, "to": 20}
+  ]
+}"##,
+        )
+        .unwrap();
+
+        let Chart::XY(ref xychart) = chart else {
+            panic!("expected an xy chart");
+        };
+        assert_eq!(xychart.config.as_ref().unwrap().theme, "base");
+        assert_eq!(xychart.series[0].data, vec![Some(40.0), None]);
+        assert_eq!(xychart.series[1].axis, crate::xychart::AxisSide::Right);
+        assert_eq!(
+            xychart.annotations[1].kind,
+            crate::xychart::AnnotationKind::Band {
+                from: 10.0,
+                to: 20.0
+            }
+        );
+        assert_eq!(
+            Chart::from_json(&serde_json::to_string(&chart).unwrap()).unwrap(),
+            chart
+        );
+
+        let chart = Chart::from_json(
+            r#"{
+  "type": "work-item-movement",
+  "columns": ["To Do", "Done"],
+  "sortBy": "id",
+  "items": [
+    {"id": "PJ-1", "states": [{"name": "To Do", "points": 1}, {"name": "Done", "points": 2}]}
+  ]
+}"#,
+        )
+        .unwrap();
+        assert_eq!(chart.chart_type(), ChartType::WorkItemMovement);
+        assert!(Chart::from_json(r#"{"type": "pie", "data": [{"label": "Done"}]}"#).is_err());
+        assert!(Chart::from_json(
+            r#"{"type": "work-item-movement", "columns": ["To Do"], "items": [{"id": "PJ-1", "states": [{"name": "To Do", "points": 1}]}]}"#
+        )
+        .is_err());
+    }
+}
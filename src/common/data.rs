@@ -0,0 +1,210 @@
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct DataError {
+    pub message: String,
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for DataError {}
+
+fn data_error(message: impl Into<String>) -> DataError {
+    DataError {
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataFormat {
+    Csv,
+    Json,
+}
+
+impl DataFormat {
+    /// Detect the format from a `.csv` or `.json` file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        match Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Some(DataFormat::Csv),
+            Some("json") => Some(DataFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Named columns of values read from a data file, which charts can be built from without
+/// writing mermaid syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataTable {
+    pub columns: Vec<String>,
+    /// Each row has a value for every column, missing values are empty
+    pub rows: Vec<Vec<String>>,
+}
+
+impl DataTable {
+    pub fn parse(content: &str, format: DataFormat) -> Result<Self, DataError> {
+        match format {
+            DataFormat::Csv => Self::from_csv(content),
+            DataFormat::Json => Self::from_json(content),
+        }
+    }
+
+    /// Read CSV with a header row, values can be quoted to include commas and quotes
+    pub fn from_csv(content: &str) -> Result<Self, DataError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+
+        let columns: Vec<String> = reader
+            .headers()
+            .map_err(|e| data_error(format!("Invalid CSV header: {}", e)))?
+            .iter()
+            .map(str::to_string)
+            .collect();
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| data_error(format!("Invalid CSV: {}", e)))?;
+            let mut row: Vec<String> = record.iter().map(str::to_string).collect();
+            row.resize(columns.len(), String::new());
+            rows.push(row);
+        }
+
+        Ok(DataTable { columns, rows })
+    }
+
+    /// Read a JSON array of objects, the columns are the keys in the order they are first seen
+    pub fn from_json(content: &str) -> Result<Self, DataError> {
+        let root: Value = serde_json::from_str(content)
+            .map_err(|e| data_error(format!("Invalid JSON: {}", e)))?;
+        let objects = root
+            .as_array()
+            .ok_or_else(|| data_error("Expected a JSON array of objects"))?;
+
+        let mut columns: Vec<String> = Vec::new();
+        for object in objects {
+            let object = object
+                .as_object()
+                .ok_or_else(|| data_error("Expected a JSON array of objects"))?;
+            for key in object.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+
+        let rows = objects
+            .iter()
+            .map(|object| {
+                columns
+                    .iter()
+                    .map(|column| match &object[column] {
+                        Value::Null => String::new(),
+                        Value::String(text) => text.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(DataTable { columns, rows })
+    }
+
+    pub fn column_index(&self, name: &str) -> Result<usize, DataError> {
+        self.columns
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| {
+                data_error(format!(
+                    "Column '{}' does not exist. Available columns are: {:?}",
+                    name, self.columns
+                ))
+            })
+    }
+
+    pub fn text_column(&self, name: &str) -> Result<Vec<String>, DataError> {
+        let index = self.column_index(name)?;
+        Ok(self.rows.iter().map(|row| row[index].clone()).collect())
+    }
+
+    /// The values of a column as numbers, empty values are `None`
+    pub fn number_column(&self, name: &str) -> Result<Vec<Option<f64>>, DataError> {
+        let index = self.column_index(name)?;
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                let value = &row[index];
+                if value.is_empty() {
+                    return Ok(None);
+                }
+                value.parse().map(Some).map_err(|_| {
+                    data_error(format!(
+                        "Column '{}' has the value '{}' in row {} which is not a number",
+                        name,
+                        value,
+                        row_index + 1
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_table_from_csv() {
+        let table = DataTable::from_csv(
+            "week, done, review\n\"Week 1, early\", 3, 2\n'Week 2', 5\n\"Say \"\"hi\"\"\",,1\n",
+        )
+        .unwrap();
+
+        assert_eq!(table.columns, vec!["week", "done", "review"]);
+        assert_eq!(
+            table.text_column("week").unwrap(),
+            vec!["Week 1, early", "'Week 2'", "Say \"hi\""]
+        );
+        assert_eq!(
+            table.number_column("done").unwrap(),
+            vec![Some(3.0), Some(5.0), None]
+        );
+        assert_eq!(
+            table.number_column("review").unwrap(),
+            vec![Some(2.0), None, Some(1.0)]
+        );
+        assert!(table.number_column("week").is_err());
+        assert!(table.column_index("missing").is_err());
+    }
+
+    #[test]
+    fn test_data_table_from_json() {
+        let table = DataTable::from_json(
+            r#"[{"week": "W1", "done": 3}, {"week": "W2", "review": 1.5, "done": null}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(table.columns, vec!["week", "done", "review"]);
+        assert_eq!(table.text_column("week").unwrap(), vec!["W1", "W2"]);
+        assert_eq!(table.number_column("done").unwrap(), vec![Some(3.0), None]);
+        assert_eq!(
+            table.number_column("review").unwrap(),
+            vec![None, Some(1.5)]
+        );
+        assert!(DataTable::from_json(r#"{"week": "W1"}"#).is_err());
+    }
+}
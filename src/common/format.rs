@@ -0,0 +1,118 @@
+/// A value that can be substituted into a template
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateValue<'a> {
+    Text(&'a str),
+    /// A number along with the decimals to show when the placeholder has no format spec
+    Number(f64, Option<usize>),
+}
+
+/// Fill in the `{name}` placeholders of a template such as `{label}: {value} ({percent}%)`.
+/// Number placeholders can have a format spec after a colon where `,` adds thousands separators
+/// and `.N` shows N decimals, e.g. `{value:,.1}`. Unknown placeholders are left as they are.
+pub fn format_template(template: &str, values: &[(&str, TemplateValue)]) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let placeholder = &rest[1..end];
+        let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, TemplateValue::Text(text))) => output.push_str(text),
+            Some((_, TemplateValue::Number(value, default_decimals))) => {
+                output.push_str(&format_number(*value, spec, *default_decimals))
+            }
+            None => output.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Format a number using a spec of an optional `,` for thousands separators followed by an
+/// optional `.N` for the number of decimals
+pub fn format_number(value: f64, spec: &str, default_decimals: Option<usize>) -> String {
+    let (separators, decimals_spec) = match spec.strip_prefix(',') {
+        Some(rest) => (true, rest),
+        None => (false, spec),
+    };
+    let decimals = decimals_spec
+        .strip_prefix('.')
+        .and_then(|decimals| decimals.parse::<usize>().ok())
+        .or(default_decimals);
+
+    let formatted = match decimals {
+        Some(decimals) => format!("{:.*}", decimals, value),
+        None => value.to_string(),
+    };
+    if !separators {
+        return formatted;
+    }
+
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted.as_str()),
+    };
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    match fraction {
+        Some(fraction) => format!("{}{}.{}", sign, grouped, fraction),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_template() {
+        let values = [
+            ("label", TemplateValue::Text("Done")),
+            ("value", TemplateValue::Number(1262.6, None)),
+            ("percent", TemplateValue::Number(47.0, Some(1))),
+        ];
+
+        assert_eq!(
+            format_template("{label}: {value} ({percent}%)", &values),
+            "Done: 1262.6 (47.0%)"
+        );
+        assert_eq!(
+            format_template("{label} [{value:,.0} pts]", &values),
+            "Done [1,263 pts]"
+        );
+        assert_eq!(format_template("{percent:.0}%", &values), "47%");
+        assert_eq!(
+            format_template("{unknown} {label", &values),
+            "{unknown} {label"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(262.0, "", None), "262");
+        assert_eq!(format_number(1234567.891, ",", None), "1,234,567.891");
+        assert_eq!(format_number(1234567.891, ",.2", None), "1,234,567.89");
+        assert_eq!(format_number(-1234.0, ",", None), "-1,234");
+        assert_eq!(format_number(999.0, ",.1", None), "999.0");
+        assert_eq!(format_number(12.345, "", Some(1)), "12.3");
+    }
+}
@@ -7,17 +7,31 @@ use nom::{
 };
 use std::collections::HashMap;
 
+use string_parser::decode_entity_codes;
+
+pub mod data;
+pub mod format;
 pub mod parser;
 pub mod renderer;
 pub mod string_parser;
+pub mod writer;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ChartConfig {
+    #[cfg_attr(feature = "serde", serde(default = "default_theme"))]
     pub theme: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub theme_variables: HashMap<String, String>,
     pub width: Option<u32>,
 }
 
+#[cfg(feature = "serde")]
+fn default_theme() -> String {
+    "base".to_string()
+}
+
 pub fn quoted_string(input: &str) -> IResult<&str, &str> {
     delimited(char('"'), take_until("\""), char('"'))(input)
 }
@@ -46,7 +60,7 @@ pub fn config_line(input: &str) -> IResult<&str, ChartConfig> {
     if let Some(theme_start) = config_content.find("'theme': '") {
         let theme_content = &config_content[theme_start + 10..];
         if let Some(theme_end) = theme_content.find("'") {
-            theme = theme_content[..theme_end].to_string();
+            theme = decode_entity_codes(&theme_content[..theme_end]);
         }
     }
 
@@ -90,6 +104,11 @@ fn parse_theme_variables(content: &str, theme_variables: &mut HashMap<String, St
 
     while let Some(ch) = chars.next() {
         match ch {
+            '{' | '}' if in_quotes => {
+                if in_value {
+                    current_value.push(ch);
+                }
+            }
             '{' => {
                 brace_count += 1;
                 if brace_count == 1 && !in_quotes {
@@ -98,16 +117,22 @@ fn parse_theme_variables(content: &str, theme_variables: &mut HashMap<String, St
                         // Parse nested object
                         let mut nested_content = String::new();
                         let mut nested_brace_count = 1;
-                        while let Some(nested_ch) = chars.next() {
-                            if nested_ch == '{' && !in_quotes {
+                        let mut nested_quote = None;
+                        for nested_ch in chars.by_ref() {
+                            if nested_ch == '{' && nested_quote.is_none() {
                                 nested_brace_count += 1;
-                            } else if nested_ch == '}' && !in_quotes {
+                            } else if nested_ch == '}' && nested_quote.is_none() {
                                 nested_brace_count -= 1;
                                 if nested_brace_count == 0 {
                                     break;
                                 }
-                            } else if nested_ch == '"' {
-                                in_quotes = !in_quotes;
+                            } else if nested_ch == '"' || nested_ch == '\'' {
+                                // Braces are part of the value until the quote it started with
+                                match nested_quote {
+                                    None => nested_quote = Some(nested_ch),
+                                    Some(quote) if quote == nested_ch => nested_quote = None,
+                                    Some(_) => {}
+                                }
                             }
                             nested_content.push(nested_ch);
                         }
@@ -133,7 +158,8 @@ fn parse_theme_variables(content: &str, theme_variables: &mut HashMap<String, St
                     if !current_key.is_empty() && !current_value.is_empty() {
                         let key = current_key.trim().trim_matches('\'').trim_matches('"');
                         let value = current_value.trim().trim_matches('\'').trim_matches('"');
-                        theme_variables.insert(key.to_string(), value.to_string());
+                        theme_variables
+                            .insert(decode_entity_codes(key), decode_entity_codes(value));
                     }
                     break;
                 } else if !in_quotes {
@@ -156,7 +182,7 @@ fn parse_theme_variables(content: &str, theme_variables: &mut HashMap<String, St
                 if !current_key.is_empty() && !current_value.is_empty() {
                     let key = current_key.trim().trim_matches('\'').trim_matches('"');
                     let value = current_value.trim().trim_matches('\'').trim_matches('"');
-                    theme_variables.insert(key.to_string(), value.to_string());
+                    theme_variables.insert(decode_entity_codes(key), decode_entity_codes(value));
                 }
                 current_key.clear();
                 current_value.clear();
@@ -181,7 +207,7 @@ fn parse_theme_variables(content: &str, theme_variables: &mut HashMap<String, St
     if !current_key.is_empty() && !current_value.is_empty() {
         let key = current_key.trim().trim_matches('\'').trim_matches('"');
         let value = current_value.trim().trim_matches('\'').trim_matches('"');
-        theme_variables.insert(key.to_string(), value.to_string());
+        theme_variables.insert(decode_entity_codes(key), decode_entity_codes(value));
     }
 }
 
@@ -191,7 +217,7 @@ fn parse_nested_theme_variables(
     theme_variables: &mut HashMap<String, String>,
 ) {
     // Parse nested key-value pairs, handling quoted strings with commas
-    let mut chars = content.chars().peekable();
+    let chars = content.chars().peekable();
     let mut current_key = String::new();
     let mut current_value = String::new();
     let mut in_key = false;
@@ -199,7 +225,7 @@ fn parse_nested_theme_variables(
     let mut in_quotes = false;
     let mut quote_char = '"';
 
-    while let Some(ch) = chars.next() {
+    for ch in chars {
         match ch {
             '"' | '\'' => {
                 if !in_quotes {
@@ -218,8 +244,8 @@ fn parse_nested_theme_variables(
                 if !current_key.is_empty() && !current_value.is_empty() {
                     let key = current_key.trim();
                     let value = current_value.trim();
-                    let full_key = format!("{}.{}", prefix, key);
-                    theme_variables.insert(full_key, value.to_string());
+                    let full_key = decode_entity_codes(&format!("{}.{}", prefix, key));
+                    theme_variables.insert(full_key, decode_entity_codes(value));
                 }
                 current_key.clear();
                 current_value.clear();
@@ -244,7 +270,7 @@ fn parse_nested_theme_variables(
     if !current_key.is_empty() && !current_value.is_empty() {
         let key = current_key.trim();
         let value = current_value.trim();
-        let full_key = format!("{}.{}", prefix, key);
-        theme_variables.insert(full_key, value.to_string());
+        let full_key = decode_entity_codes(&format!("{}.{}", prefix, key));
+        theme_variables.insert(full_key, decode_entity_codes(value));
     }
 }
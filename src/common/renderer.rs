@@ -1,6 +1,10 @@
 use crate::font::measure_text_width;
 use svg::node::element::{Group, Rectangle, Text};
 
+/// The largest share of the available width that a legend beside the chart can take up before
+/// it is moved below the chart
+const MAX_BESIDE_LEGEND_WIDTH_RATIO: f64 = 0.5;
+
 /// Configuration for legend rendering
 pub struct LegendConfig {
     pub font_name: String,
@@ -10,7 +14,10 @@ pub struct LegendConfig {
     pub icon_to_text_gap: f64,
     pub item_spacing: f64,
     pub right_margin: f64,
+    pub column_gap: f64,
     pub draw_border: bool,
+    pub border_color: String,
+    pub icon_opacity: f64,
 }
 
 impl Default for LegendConfig {
@@ -23,9 +30,122 @@ impl Default for LegendConfig {
             icon_to_text_gap: 4.0,
             item_spacing: 22.0,
             right_margin: 20.0,
+            column_gap: 20.0,
             draw_border: true,
+            border_color: "#000000".to_string(),
+            icon_opacity: 1.0,
+        }
+    }
+}
+
+/// Where the legend is placed relative to the chart
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LegendPosition {
+    #[default]
+    Right,
+    Left,
+    Top,
+    Bottom,
+    None,
+}
+
+impl LegendPosition {
+    /// Whether the legend is beside the chart rather than above or below it
+    pub fn is_beside(&self) -> bool {
+        matches!(self, LegendPosition::Right | LegendPosition::Left)
+    }
+}
+
+/// Parse a legend position, warning and falling back to the right for unknown positions
+pub fn parse_legend_position(value: &str) -> LegendPosition {
+    match value {
+        "right" => LegendPosition::Right,
+        "left" => LegendPosition::Left,
+        "top" => LegendPosition::Top,
+        "bottom" => LegendPosition::Bottom,
+        "none" => LegendPosition::None,
+        _ => {
+            eprintln!("Warning: Invalid legend position '{}', using right", value);
+            LegendPosition::Right
+        }
+    }
+}
+
+/// The arrangement of legend items into columns and rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendLayout {
+    /// The position the legend was laid out for, which is below the chart when a legend beside
+    /// it would be too wide
+    pub position: LegendPosition,
+    pub columns: usize,
+    pub rows: usize,
+    /// Width of each column, which is the width of the widest item
+    pub column_width: f64,
+    /// Items fill each column before moving to the next one rather than filling each row
+    pub column_major: bool,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Lay out the legend items for a position. Legends beside the chart wrap into more columns
+/// when they are taller than `max_height` and move below the chart when those columns would
+/// take up more than half of `max_width`, legends above or below the chart wrap into more rows
+/// when they are wider than `max_width`.
+pub fn layout_legend(
+    labels: &[String],
+    font_data: &Option<Vec<u8>>,
+    config: &LegendConfig,
+    position: LegendPosition,
+    max_width: f64,
+    max_height: f64,
+) -> LegendLayout {
+    if labels.is_empty() || position == LegendPosition::None {
+        return LegendLayout {
+            position,
+            columns: 0,
+            rows: 0,
+            column_width: 0.0,
+            column_major: true,
+            width: 0.0,
+            height: 0.0,
+        };
+    }
+
+    let column_width = calculate_legend_width(labels, font_data, config) - config.right_margin;
+    let (columns, column_major) = if position.is_beside() {
+        let max_rows = ((max_height / config.item_spacing).floor() as usize).max(1);
+        (labels.len().div_ceil(max_rows), true)
+    } else {
+        let max_columns =
+            ((max_width + config.column_gap) / (column_width + config.column_gap)).floor() as usize;
+        (max_columns.clamp(1, labels.len()), false)
+    };
+    let rows = labels.len().div_ceil(columns);
+
+    let mut width = columns as f64 * column_width + (columns - 1) as f64 * config.column_gap;
+    if position.is_beside() {
+        width += config.right_margin;
+        if width > max_width * MAX_BESIDE_LEGEND_WIDTH_RATIO {
+            return layout_legend(
+                labels,
+                font_data,
+                config,
+                LegendPosition::Bottom,
+                max_width,
+                max_height,
+            );
         }
     }
+
+    LegendLayout {
+        position,
+        columns,
+        rows,
+        column_width,
+        column_major,
+        width,
+        height: rows as f64 * config.item_spacing,
+    }
 }
 
 /// Calculate the width needed for the legend
@@ -55,30 +175,39 @@ pub fn calculate_legend_width(
     icon_total_width + max_text_width + config.right_margin
 }
 
-/// Render a legend at the specified position
+/// Render a legend at the specified position with its items arranged by the layout
 pub fn render_legend(
     labels: &[String],
     colors: &[String],
     x: f64,
     y: f64,
+    layout: &LegendLayout,
     config: &LegendConfig,
 ) -> Group {
     let mut legend_group = Group::new().set("class", "legend");
 
     for (i, label) in labels.iter().enumerate() {
-        let item_y = y + (i as f64 * config.item_spacing);
+        let (column, row) = if layout.column_major {
+            (i / layout.rows, i % layout.rows)
+        } else {
+            (i % layout.columns, i / layout.columns)
+        };
+        let item_x = x + column as f64 * (layout.column_width + config.column_gap);
+        let item_y = y + (row as f64 * config.item_spacing);
         let color = colors.get(i).map(|c| c.as_str()).unwrap_or("#000000");
 
-        let item_group = Group::new().set("transform", format!("translate({},{})", x, item_y));
+        let item_group = Group::new().set("transform", format!("translate({},{})", item_x, item_y));
 
         let mut rect = Rectangle::new()
             .set("width", config.icon_width)
             .set("height", config.icon_height)
             .set("fill", color)
-            .set("fill-opacity", "1");
+            .set("fill-opacity", config.icon_opacity);
 
         if config.draw_border {
-            rect = rect.set("stroke", "#000000").set("stroke-width", "1px");
+            rect = rect
+                .set("stroke", config.border_color.as_str())
+                .set("stroke-width", "1px");
         }
 
         let item_group = item_group.add(rect).add(
@@ -99,3 +228,82 @@ pub fn render_legend(
 pub fn calculate_legend_height(num_items: usize, config: &LegendConfig) -> f64 {
     num_items as f64 * config.item_spacing
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("Item {}", i)).collect()
+    }
+
+    /// Without font data every character is 8 pixels wide, so each of these items is 70 wide
+    fn layout(
+        count: usize,
+        position: LegendPosition,
+        max_width: f64,
+        max_height: f64,
+    ) -> LegendLayout {
+        layout_legend(
+            &labels(count),
+            &None,
+            &LegendConfig::default(),
+            position,
+            max_width,
+            max_height,
+        )
+    }
+
+    #[test]
+    fn test_layout_legend_beside() {
+        let legend = layout(5, LegendPosition::Right, 800.0, 500.0);
+        assert_eq!(legend.position, LegendPosition::Right);
+        assert_eq!((legend.columns, legend.rows), (1, 5));
+        assert!(legend.column_major);
+        assert_eq!(legend.height, 5.0 * 22.0);
+
+        // Items that don't fit in the height wrap into columns that are filled first
+        let legend = layout(5, LegendPosition::Left, 800.0, 50.0);
+        assert_eq!(legend.position, LegendPosition::Left);
+        assert_eq!((legend.columns, legend.rows), (3, 2));
+        assert!(legend.column_major);
+        assert_eq!(legend.width, 3.0 * 70.0 + 2.0 * 20.0 + 20.0);
+    }
+
+    #[test]
+    fn test_layout_legend_beside_too_wide() {
+        // Three columns beside the chart would take up most of its width so the legend moves
+        // below it instead
+        let legend = layout(5, LegendPosition::Right, 400.0, 50.0);
+        assert_eq!(legend.position, LegendPosition::Bottom);
+        assert_eq!((legend.columns, legend.rows), (4, 2));
+        assert!(!legend.column_major);
+        assert!(legend.width <= 400.0);
+    }
+
+    #[test]
+    fn test_layout_legend_above_or_below() {
+        let legend = layout(5, LegendPosition::Top, 800.0, 500.0);
+        assert_eq!((legend.columns, legend.rows), (5, 1));
+
+        // Items that don't fit in the width wrap into rows that are filled first
+        let legend = layout(5, LegendPosition::Bottom, 200.0, 500.0);
+        assert_eq!((legend.columns, legend.rows), (2, 3));
+        assert!(!legend.column_major);
+        assert_eq!(legend.height, 3.0 * 22.0);
+
+        // A single item wider than the chart still gets a column
+        let legend = layout(1, LegendPosition::Bottom, 50.0, 500.0);
+        assert_eq!((legend.columns, legend.rows), (1, 1));
+    }
+
+    #[test]
+    fn test_layout_legend_none() {
+        let legend = layout(5, LegendPosition::None, 800.0, 500.0);
+        assert_eq!((legend.columns, legend.rows), (0, 0));
+        assert_eq!((legend.width, legend.height), (0.0, 0.0));
+
+        let legend = layout(0, LegendPosition::Right, 800.0, 500.0);
+        assert_eq!((legend.columns, legend.rows), (0, 0));
+    }
+}
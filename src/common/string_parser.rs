@@ -1,7 +1,10 @@
 use nom::{
-    bytes::complete::take_until,
+    branch::alt,
+    bytes::complete::{take_until, take_while1},
     character::complete::{char, multispace0},
-    sequence::delimited,
+    combinator::map,
+    multi::separated_list0,
+    sequence::{delimited, tuple},
     IResult,
 };
 
@@ -15,19 +18,51 @@ pub fn quoted_string_single(input: &str) -> IResult<&str, &str> {
     delimited(char('\''), take_until("'"), char('\''))(input)
 }
 
+/// The character of the entity code at the start of the text, e.g. `#quot;` or `#35;`, along
+/// with the length of the code
+pub fn entity_code(text: &str) -> Option<(char, usize)> {
+    let code = text.strip_prefix('#')?;
+    let end = code.find(';')?;
+    let ch = match &code[..end] {
+        "quot" => '"',
+        digits if (1..=7).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            char::from_u32(digits.parse().ok()?)?
+        }
+        _ => return None,
+    };
+    Some((ch, end + 2))
+}
+
+/// Replace the entity codes in quoted text with their characters, which is how quoted text
+/// contains both kinds of quote
+pub fn decode_entity_codes(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('#') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match entity_code(rest) {
+            Some((ch, length)) => {
+                decoded.push(ch);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('#');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
 /// Parse a label that can be either quoted (single or double quotes) or unquoted
 /// When quoted, the label can contain commas
 pub fn parse_label(input: &str) -> IResult<&str, String> {
     let (input, _) = multispace0(input)?;
 
-    // Try parsing as double-quoted string
-    if let Ok((input, content)) = quoted_string(input) {
-        return Ok((input, content.to_string()));
-    }
-
-    // Try parsing as single-quoted string
-    if let Ok((input, content)) = quoted_string_single(input) {
-        return Ok((input, content.to_string()));
+    if let Ok((input, content)) = any_quoted_string(input) {
+        return Ok((input, content));
     }
 
     // Parse as unquoted string (until comma or closing bracket)
@@ -77,6 +112,47 @@ pub fn parse_labels_list(input: &str) -> IResult<&str, Vec<String>> {
     Ok((remaining, labels))
 }
 
+/// Parse a string in either double or single quotes, decoding its entity codes
+pub fn any_quoted_string(input: &str) -> IResult<&str, String> {
+    map(
+        alt((quoted_string, quoted_string_single)),
+        decode_entity_codes,
+    )(input)
+}
+
+/// Parse a single `key: value` attribute, the value can be quoted or unquoted
+fn attribute(input: &str) -> IResult<&str, (String, String)> {
+    let (input, _) = multispace0(input)?;
+    let (input, key) = take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(':')(input)?;
+    let (input, _) = multispace0(input)?;
+
+    if let Ok((input, value)) = any_quoted_string(input) {
+        return Ok((input, (key.to_string(), value)));
+    }
+
+    let (input, value) = take_until_any(&[',', '}'])(input)?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::TakeWhile1,
+        )));
+    }
+    Ok((input, (key.to_string(), value.to_string())))
+}
+
+/// Parse a list of attributes enclosed in braces, e.g. `{color: #038411, stroke: dashed}`
+pub fn parse_attributes(input: &str) -> IResult<&str, Vec<(String, String)>> {
+    let (input, _) = char('{')(input)?;
+    let (input, attributes) = separated_list0(tuple((multispace0, char(','))), attribute)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char('}')(input)?;
+
+    Ok((input, attributes))
+}
+
 /// Take until any of the specified characters is found
 pub fn take_until_any(chars: &[char]) -> impl Fn(&str) -> IResult<&str, &str> + '_ {
     move |input: &str| {
@@ -121,6 +197,20 @@ mod tests {
         assert_eq!(label, "SimpleLabel");
     }
 
+    #[test]
+    fn test_decode_entity_codes() {
+        assert_eq!(decode_entity_codes("Say #quot;it's#quot;"), "Say \"it's\"");
+        assert_eq!(decode_entity_codes("#35;quot; #9829;"), "#quot; \u{2665}");
+        assert_eq!(
+            decode_entity_codes("#12 #ff0000; #; #x; ##39;"),
+            "#12 #ff0000; #; #x; #'"
+        );
+        assert_eq!(decode_entity_codes("#99999999;"), "#99999999;");
+
+        let (_, label) = parse_label(r#""A #quot;B#quot; 'C'""#).unwrap();
+        assert_eq!(label, r#"A "B" 'C'"#);
+    }
+
     #[test]
     fn test_parse_labels_list() {
         // Test mixed quoted and unquoted labels
@@ -135,6 +225,28 @@ mod tests {
         assert_eq!(labels[3], "Another, Label");
     }
 
+    #[test]
+    fn test_parse_attributes() {
+        let result = parse_attributes(r#"{color: #038411, points: square, label: "A, B"}"#);
+        assert!(result.is_ok());
+        let (remaining, attributes) = result.unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(
+            attributes,
+            vec![
+                ("color".to_string(), "#038411".to_string()),
+                ("points".to_string(), "square".to_string()),
+                ("label".to_string(), "A, B".to_string()),
+            ]
+        );
+
+        let result = parse_attributes("{}");
+        assert!(result.is_ok());
+        assert!(result.unwrap().1.is_empty());
+
+        assert!(parse_attributes("{color: }").is_err());
+    }
+
     #[test]
     fn test_take_until_any() {
         let parser = take_until_any(&[',', ']']);
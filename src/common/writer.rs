@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::string_parser::entity_code;
+use super::ChartConfig;
+
+/// Write the `special` characters of quoted text as entity codes, along with any `#` that would
+/// otherwise be read as the start of an entity code
+pub fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (i, ch) in text.char_indices() {
+        if ch == '"' && special.contains(&ch) {
+            escaped.push_str("#quot;");
+        } else if special.contains(&ch) || (ch == '#' && entity_code(&text[i..]).is_some()) {
+            escaped.push_str(&format!("#{};", ch as u32));
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped
+}
+
+/// Double quote a string, writing its double quotes as `#quot;` which mermaid decodes as well
+pub fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text, &['"']))
+}
+
+/// Single quote a string of the config, which can't contain either quote
+fn quote_config(text: &str) -> String {
+    format!("'{}'", escape(text, &['"', '\'']))
+}
+
+/// Write a list of labels such as the x-axis, only quoting the labels that need it
+pub fn labels_list(labels: &[String]) -> String {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|label| {
+            if label.trim().is_empty()
+                || label.trim() != label
+                || label.starts_with(['"', '\''])
+                || label.contains([',', ']'])
+            {
+                quote(label)
+            } else {
+                label.clone()
+            }
+        })
+        .collect();
+    format!("[{}]", labels.join(", "))
+}
+
+/// Write the config as the `%%{init}%%` line that precedes a chart. Theme variables are sorted
+/// and keys such as `xyChart.plotColorPalette` are written as nested objects. Strings are single
+/// quoted with any quotes in them written as entity codes.
+impl fmt::Display for ChartConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "%%{{init: {{'theme': {}", quote_config(&self.theme))?;
+        if let Some(width) = self.width {
+            write!(f, ", 'width': {}", width)?;
+        }
+
+        if !self.theme_variables.is_empty() {
+            let mut entries: Vec<(&str, String)> = Vec::new();
+            let mut nested: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+            for (key, value) in &self.theme_variables {
+                match key.split_once('.') {
+                    Some((prefix, key)) => nested.entry(prefix).or_default().push((key, value)),
+                    None => entries.push((
+                        key,
+                        format!("{}: {}", quote_config(key), quote_config(value)),
+                    )),
+                }
+            }
+            for (prefix, mut values) in nested {
+                values.sort();
+                let values: Vec<String> = values
+                    .into_iter()
+                    .map(|(key, value)| format!("{}: {}", quote_config(key), quote_config(value)))
+                    .collect();
+                entries.push((
+                    prefix,
+                    format!("{}: {{{}}}", quote_config(prefix), values.join(", ")),
+                ));
+            }
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            let entries: Vec<String> = entries.into_iter().map(|(_, entry)| entry).collect();
+            write!(f, ", 'themeVariables': {{{}}}", entries.join(", "))?;
+        }
+
+        write!(f, "}}}}%%")
+    }
+}
+
+/// Strategies for the round trip tests of the chart writers
+#[cfg(test)]
+pub(crate) mod strategies {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    /// Theme variable keys and values that the config parser reads back
+    fn theme_variables() -> impl Strategy<Value = HashMap<String, String>> {
+        prop::collection::hash_map(
+            "[a-z][a-zA-Z0-9]{0,6}(\\.[a-z][a-zA-Z0-9]{0,6})?"
+                .prop_filter("reserved key", |key| key != "theme" && key != "width"),
+            "[a-zA-Z0-9#;'\"{}:,.]([a-zA-Z0-9#;'\"{}:,. ]{0,10}[a-zA-Z0-9#;'\"{}:,.])?",
+            0..6,
+        )
+        .prop_filter("key is also a prefix", |variables| {
+            variables.keys().all(|key| {
+                !variables
+                    .keys()
+                    .any(|other| other.starts_with(&format!("{}.", key)))
+            })
+        })
+    }
+
+    pub fn chart_config() -> impl Strategy<Value = ChartConfig> {
+        (
+            "[a-z]{1,8}",
+            theme_variables(),
+            prop::option::of(1u32..5000),
+        )
+            .prop_map(|(theme, theme_variables, width)| ChartConfig {
+                theme,
+                theme_variables,
+                width,
+            })
+    }
+
+    /// Text to be quoted, which can contain both kinds of quote and text that looks like an
+    /// entity code
+    pub fn quotable_text() -> impl Strategy<Value = String> {
+        prop::collection::vec(
+            prop_oneof![
+                "[a-zA-Z0-9 ,:;#'\"(){}\\[\\]>-]",
+                Just("#quot;".to_string()),
+                Just("#35;".to_string()),
+            ],
+            0..12,
+        )
+        .prop_map(|parts| parts.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strategies::chart_config;
+    use super::*;
+    use crate::common::config_line;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_write_chart_config() {
+        let config = ChartConfig {
+            theme: "base".to_string(),
+            theme_variables: HashMap::from([
+                ("pie1".to_string(), "#ff0000".to_string()),
+                (
+                    "xyChart.plotColorPalette".to_string(),
+                    "#ff8b00, #9c1de9".to_string(),
+                ),
+                ("pieCenterText".to_string(), "{total} points".to_string()),
+                ("xyChart.titleColor".to_string(), "{blue}".to_string()),
+            ]),
+            width: Some(900),
+        };
+
+        assert_eq!(
+            config.to_string(),
+            "%%{init: {'theme': 'base', 'width': 900, 'themeVariables': {'pie1': '#ff0000', \
+             'pieCenterText': '{total} points', 'xyChart': {'plotColorPalette': '#ff8b00, #9c1de9', \
+             'titleColor': '{blue}'}}}}%%"
+        );
+        assert_eq!(config_line(&config.to_string()).unwrap().1, config);
+    }
+
+    #[test]
+    fn test_write_chart_config_quotes() {
+        let config = ChartConfig {
+            theme: "base".to_string(),
+            theme_variables: HashMap::from([
+                ("pieCenterText".to_string(), "It's \"done\"".to_string()),
+                ("pieTitleText".to_string(), "#quot;".to_string()),
+            ]),
+            width: None,
+        };
+
+        assert_eq!(
+            config.to_string(),
+            "%%{init: {'theme': 'base', 'themeVariables': {'pieCenterText': 'It#39;s #quot;done#quot;', \
+             'pieTitleText': '#35;quot;'}}}%%"
+        );
+        assert_eq!(config_line(&config.to_string()).unwrap().1, config);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("It's"), "\"It's\"");
+        assert_eq!(quote("Say \"hi\""), "\"Say #quot;hi#quot;\"");
+        assert_eq!(quote("It's \"hi\""), "\"It's #quot;hi#quot;\"");
+        assert_eq!(quote("#12 #34;"), "\"#12 #35;34;\"");
+    }
+
+    proptest! {
+        #[test]
+        fn test_chart_config_round_trip(config in chart_config()) {
+            let written = config.to_string();
+            let (remaining, parsed) = config_line(&written).unwrap();
+            prop_assert_eq!(remaining, "");
+            prop_assert_eq!(parsed, config);
+        }
+    }
+}
@@ -2,9 +2,26 @@ use font_kit::family_handle::FamilyHandle;
 use font_kit::handle::Handle;
 use font_kit::source::SystemSource;
 use rusttype::{Font, Scale};
+use std::collections::HashMap;
 use std::fs;
+use std::sync::{Mutex, OnceLock};
 
+/// Fonts that have been looked up by name, including the ones that weren't found, so that
+/// rendering many charts only scans the system fonts once for each font
+static FONT_CACHE: OnceLock<Mutex<HashMap<String, Option<Vec<u8>>>>> = OnceLock::new();
+
+/// Load a system font by its postscript or family name, caching it for later charts
 pub fn load_system_font_bytes(font_name: &str) -> Option<Vec<u8>> {
+    // The lock is held while the font is found so that charts rendered in parallel don't all
+    // scan the system fonts for it
+    let mut cache = FONT_CACHE.get_or_init(Default::default).lock().unwrap();
+    cache
+        .entry(font_name.to_string())
+        .or_insert_with(|| find_system_font_bytes(font_name))
+        .clone()
+}
+
+fn find_system_font_bytes(font_name: &str) -> Option<Vec<u8>> {
     let source = SystemSource::new();
     let handle = source
         .select_by_postscript_name(font_name)
@@ -13,7 +30,7 @@ pub fn load_system_font_bytes(font_name: &str) -> Option<Vec<u8>> {
             source
                 .select_family_by_name(font_name)
                 .ok()
-                .and_then(|family: FamilyHandle| family.fonts().get(0).cloned())
+                .and_then(|family: FamilyHandle| family.fonts().first().cloned())
         })?;
 
     let path = match handle {
@@ -41,3 +58,23 @@ pub fn measure_text_height(font_data: &[u8], pixel_height: f32) -> f32 {
     // Return the total height (ascent + descent)
     v_metrics.ascent - v_metrics.descent
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_system_font_bytes_is_cached() {
+        let font_name = "Pisnge Test Font";
+        assert_eq!(load_system_font_bytes(font_name), None);
+
+        // Later loads come from the cache rather than the system fonts
+        FONT_CACHE
+            .get()
+            .unwrap()
+            .lock()
+            .unwrap()
+            .insert(font_name.to_string(), Some(vec![1, 2, 3]));
+        assert_eq!(load_system_font_bytes(font_name), Some(vec![1, 2, 3]));
+    }
+}
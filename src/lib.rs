@@ -1,12 +1,19 @@
+pub mod batch;
+pub mod chart;
 pub mod common;
 pub mod font;
+pub mod markdown;
 pub mod pie_chart;
 pub mod png;
 pub mod work_item_movement;
 pub mod xychart;
 
+pub use chart::{Chart, ChartError};
+
 // Re-export pie chart types for backward compatibility
 pub use pie_chart::{PieChart, PieChartConfig, PieChartData};
 
 // Re-export xychart types
-pub use xychart::{Series, SeriesType, XAxis, XYChart, YAxis};
+pub use xychart::{
+    Annotation, AnnotationKind, AxisSide, Series, SeriesType, XAxis, XYChart, YAxis,
+};
@@ -1,30 +1,81 @@
-use clap::Parser;
-use pisnge::common::parser::{parse_config_and_detect_type, ChartType};
-use pisnge::pie_chart::{parse_pie_chart_content, render_pie_chart_svg};
-use pisnge::png::svg_to_png;
-use pisnge::work_item_movement::{
-    parse_work_item_movement, render_work_item_movement_svg, validate_work_item_movement,
-};
-use pisnge::xychart::{parse_xychart_content, render_xychart_svg};
+use clap::{Args, Parser, Subcommand};
+use pisnge::batch::{batch_items, map_parallel, BatchItem};
+use pisnge::common::data::{DataFormat, DataTable};
+use pisnge::markdown::{find_mermaid_blocks, image_link, replace_ranges};
+use pisnge::pie_chart::{pie_chart_from_data, PieDataSpec};
+use pisnge::png::{load_font_database, svg_to_png, svg_to_png_with_fonts};
+use pisnge::work_item_movement::{import_jira_changelog, JiraImportOptions, DEFAULT_POINTS_FIELD};
+use pisnge::xychart::{xychart_from_data, SeriesType, XYDataSpec};
+use pisnge::Chart;
+use resvg::usvg::fontdb::Database;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+use svg::Document;
 
 #[derive(Parser)]
 #[command(name = "pisnge")]
 #[command(about = "A Rust-based diagram rendering library inspired by Mermaid.js")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    #[arg(short, long)]
-    input: String,
+    #[command(subcommand)]
+    command: Option<Commands>,
 
-    #[arg(short, long)]
-    output: String,
+    #[arg(short, long, required_unless_present = "data", conflicts_with = "data")]
+    input: Option<String>,
+
+    #[arg(short, long, required = true)]
+    output: Option<String>,
 
-    #[arg(short, long, value_parser = ["png", "svg"])]
+    #[arg(short, long, value_parser = ["png", "svg", "mmd"])]
     format: Option<String>,
 
     #[arg(short, long)]
     verbose: bool,
 
+    #[command(flatten)]
+    render: RenderOptions,
+
+    /// A CSV or JSON data file to build the chart from instead of an input file
+    #[arg(long, requires = "chart_type")]
+    data: Option<String>,
+
+    /// The type of chart to build from the data file
+    #[arg(long = "type", value_parser = ["pie", "xy"], requires = "data")]
+    chart_type: Option<String>,
+
+    /// The data column of pie chart labels, defaults to the first column
+    #[arg(long)]
+    label_column: Option<String>,
+
+    /// The data column of pie chart values, defaults to the second column
+    #[arg(long)]
+    value_column: Option<String>,
+
+    /// The data column of xy chart x-axis labels, defaults to the first column
+    #[arg(long)]
+    x_column: Option<String>,
+
+    /// The data columns plotted as xy chart series, defaults to all columns other than the x column
+    #[arg(long, value_delimiter = ',')]
+    series: Vec<String>,
+
+    /// How the xy chart series are drawn
+    #[arg(long, value_parser = ["bar", "line", "area", "stacked-area"], default_value = "bar")]
+    series_type: String,
+
+    /// The title of a chart built from a data file
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Label the slices of a pie chart built from a data file with their percentages
+    #[arg(long)]
+    show_data: bool,
+}
+
+/// How charts are rendered to SVG and PNG
+#[derive(Args)]
+struct RenderOptions {
     #[arg(short, long, default_value = "800")]
     width: u32,
 
@@ -35,6 +86,303 @@ struct Cli {
     font: String,
 }
 
+#[derive(Subcommand)]
+enum Commands {
+    /// Render the mermaid code blocks of a markdown file to images linked from a copy of it
+    Markdown {
+        /// The markdown file to read
+        input: String,
+
+        /// The markdown file to write, images are written beside it as `<name>-<n>.<format>`
+        #[arg(short, long)]
+        output: String,
+
+        #[arg(short, long, value_parser = ["svg", "png"], default_value = "svg")]
+        format: String,
+
+        #[command(flatten)]
+        render: RenderOptions,
+    },
+    /// Render many charts at once into a directory
+    Render {
+        /// Input files or glob patterns such as 'reports/**/*.mmd', quoted so that the shell
+        /// doesn't expand them
+        #[arg(required = true)]
+        patterns: Vec<String>,
+
+        /// The directory to write to, outputs keep their path relative to the directory of the
+        /// pattern that matched them
+        #[arg(long)]
+        out_dir: String,
+
+        #[arg(short, long, value_parser = ["png", "svg", "mmd"], default_value = "png")]
+        format: String,
+
+        /// The number of charts to render at once, defaults to the number of cores
+        #[arg(short, long)]
+        jobs: Option<usize>,
+
+        #[command(flatten)]
+        render: RenderOptions,
+    },
+    /// Create a chart from data exported from another tool
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+    /// Create a work item movement chart from Jira issues exported with their changelog
+    JiraChangelog {
+        /// The first day of the window as YYYY-MM-DD
+        #[arg(long)]
+        from: String,
+
+        /// The last day of the window as YYYY-MM-DD
+        #[arg(long)]
+        to: String,
+
+        /// The id or name of the story points field
+        #[arg(long, default_value = DEFAULT_POINTS_FIELD)]
+        points_field: String,
+
+        /// The statuses to use as columns in order, separated by commas
+        #[arg(long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Include issues with the same status and points at the start and end of the window
+        #[arg(long)]
+        include_unchanged: bool,
+
+        /// Write the chart to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// The JSON file of issues
+        input: String,
+    },
+}
+
+fn run_import(source: ImportSource) {
+    match source {
+        ImportSource::JiraChangelog {
+            from,
+            to,
+            points_field,
+            columns,
+            include_unchanged,
+            output,
+            input,
+        } => {
+            let content = match fs::read_to_string(&input) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Failed to read input file: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let options = JiraImportOptions {
+                from,
+                to,
+                points_field,
+                columns: columns.map(|columns| {
+                    columns
+                        .into_iter()
+                        .map(|column| column.trim().to_string())
+                        .collect()
+                }),
+                include_unchanged,
+            };
+            let chart = match import_jira_changelog(&content, &options) {
+                Ok(chart) => chart,
+                Err(e) => {
+                    eprintln!("Failed to import Jira changelog: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            match output {
+                Some(output) => match fs::write(&output, chart.to_string()) {
+                    Ok(_) => eprintln!("Chart saved to: {}", output),
+                    Err(e) => {
+                        eprintln!("Failed to write chart file: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                None => print!("{}", chart),
+            }
+        }
+    }
+}
+
+/// Render a chart to the bytes of the output format
+fn render_output(
+    chart: &Chart,
+    options: &RenderOptions,
+    output_format: &str,
+    fontdb: &Arc<Database>,
+) -> Result<Vec<u8>, String> {
+    if output_format == "mmd" {
+        return Ok(chart.to_mermaid().into_bytes());
+    }
+
+    let (svg_document, width, height) =
+        chart.render_svg(options.width, options.height, &options.font);
+    if output_format == "svg" {
+        return Ok(svg_document.to_string().into_bytes());
+    }
+    svg_to_png_with_fonts(
+        &svg_document.to_string(),
+        width,
+        height,
+        &options.font,
+        fontdb.clone(),
+    )
+    .map_err(|e| format!("Failed to convert SVG to PNG: {}", e))
+}
+
+fn render_batch_item(
+    item: &BatchItem,
+    options: &RenderOptions,
+    output_format: &str,
+    fontdb: &Arc<Database>,
+) -> Result<(), String> {
+    let chart = Chart::from_file(&item.input).map_err(|e| e.to_string())?;
+    let data = render_output(&chart, options, output_format, fontdb)?;
+    if let Some(parent) = item.output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&item.output, data).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+/// Render every chart matching the patterns in parallel, a chart that fails is reported without
+/// stopping the others
+fn run_render(
+    patterns: &[String],
+    out_dir: &str,
+    output_format: &str,
+    jobs: Option<usize>,
+    options: &RenderOptions,
+) {
+    let items = match batch_items(patterns, Path::new(out_dir), output_format) {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let fontdb = if output_format == "png" {
+        load_font_database()
+    } else {
+        Arc::default()
+    };
+    let jobs = jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|jobs| jobs.get())
+            .unwrap_or(1)
+    });
+
+    let results = map_parallel(&items, jobs, |item| {
+        let result = render_batch_item(item, options, output_format, &fontdb);
+        match &result {
+            Ok(_) => println!(
+                "Rendered {} to {}",
+                item.input.display(),
+                item.output.display()
+            ),
+            Err(e) => eprintln!("Failed to render {}: {}", item.input.display(), e),
+        }
+        result
+    });
+
+    let failures = results.iter().filter(|result| result.is_err()).count();
+    println!(
+        "Rendered {} of {} charts",
+        items.len() - failures,
+        items.len()
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Render each mermaid code block of a markdown file to an image and replace the block with a
+/// link to it, blocks that can't be parsed are left as they are
+fn run_markdown(input: &str, output: &str, output_format: &str, options: &RenderOptions) {
+    let markdown = match fs::read_to_string(input) {
+        Ok(markdown) => markdown,
+        Err(e) => {
+            eprintln!("Failed to read input file: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let output_path = Path::new(output);
+    let directory = output_path.parent().unwrap_or(Path::new(""));
+    let name = output_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if let Err(e) = fs::create_dir_all(directory) {
+        eprintln!("Failed to create output directory: {}", e);
+        std::process::exit(1);
+    }
+
+    let fontdb = if output_format == "png" {
+        load_font_database()
+    } else {
+        Arc::default()
+    };
+
+    let mut replacements = Vec::new();
+    for (index, block) in find_mermaid_blocks(&markdown).iter().enumerate() {
+        let line = markdown[..block.range.start].lines().count() + 1;
+        let chart = match Chart::parse(&block.content) {
+            Ok(chart) => chart,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Leaving the mermaid block on line {} unchanged: {}",
+                    line, e
+                );
+                continue;
+            }
+        };
+
+        let image_name = format!("{}-{}.{}", name, index + 1, output_format);
+        let image_path = directory.join(&image_name);
+        let written = render_output(&chart, options, output_format, &fontdb).and_then(|data| {
+            fs::write(&image_path, data).map_err(|e| format!("Failed to write file: {}", e))
+        });
+        if let Err(e) = written {
+            eprintln!("Failed to render the mermaid block on line {}: {}", line, e);
+            std::process::exit(1);
+        }
+        println!(
+            "Rendered the mermaid block on line {} to {}",
+            line,
+            image_path.display()
+        );
+
+        let mut link =
+            " ".repeat(block.indent) + &image_link(chart.title().unwrap_or("diagram"), &image_name);
+        if markdown[block.range.clone()].ends_with('\n') {
+            link.push('\n');
+        }
+        replacements.push((block.range.clone(), link));
+    }
+
+    match fs::write(output, replace_ranges(&markdown, &replacements)) {
+        Ok(_) => println!("Markdown saved to: {}", output),
+        Err(e) => {
+            eprintln!("Failed to write markdown file: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn detect_format_from_extension(output_path: &str) -> Option<String> {
     Path::new(output_path)
         .extension()
@@ -43,328 +391,296 @@ fn detect_format_from_extension(output_path: &str) -> Option<String> {
         .and_then(|ext| match ext.as_str() {
             "png" => Some("png".to_string()),
             "svg" => Some("svg".to_string()),
+            "mmd" => Some("mmd".to_string()),
             _ => None,
         })
 }
 
+/// Write a rendered chart to the output file as an SVG or PNG
+fn write_chart(
+    svg_document: Document,
+    width: u32,
+    height: u32,
+    output: &str,
+    output_format: &str,
+    font: &str,
+) {
+    match output_format {
+        "svg" => match fs::write(output, svg_document.to_string()) {
+            Ok(_) => println!("SVG saved to: {}", output),
+            Err(e) => {
+                eprintln!("Failed to write SVG file: {}", e);
+                std::process::exit(1);
+            }
+        },
+        "png" => match svg_to_png(&svg_document.to_string(), width, height, font) {
+            Ok(png_data) => match fs::write(output, png_data) {
+                Ok(_) => println!("PNG saved to: {}", output),
+                Err(e) => {
+                    eprintln!("Failed to write PNG file: {}", e);
+                    std::process::exit(1);
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to convert SVG to PNG: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => {
+            eprintln!(
+                "Unsupported format: {}. Supported formats: png, svg, mmd",
+                output_format
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Write a chart to the output file in mermaid syntax
+fn write_mermaid(chart: &Chart, output: &str) {
+    match fs::write(output, chart.to_mermaid()) {
+        Ok(_) => println!("Chart saved to: {}", output),
+        Err(e) => {
+            eprintln!("Failed to write chart file: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Write a chart to the output file in mermaid syntax or render it to an SVG or PNG
+fn write_output(chart: &Chart, options: &RenderOptions, output: &str, output_format: &str) {
+    if output_format == "mmd" {
+        write_mermaid(chart, output);
+    } else {
+        let (svg_document, width, height) =
+            chart.render_svg(options.width, options.height, &options.font);
+        write_chart(
+            svg_document,
+            width,
+            height,
+            output,
+            output_format,
+            &options.font,
+        );
+    }
+}
+
+fn print_chart_details(chart: &Chart) {
+    println!("\nDetected chart type: {:?}", chart.chart_type());
+    if let Some(config) = chart.config() {
+        println!("Theme: {}", config.theme);
+        if !config.theme_variables.is_empty() {
+            println!("Theme variables: {:?}", config.theme_variables);
+        }
+    }
+
+    match chart {
+        Chart::Pie(pie_chart) => {
+            println!("\nParsed pie chart:");
+            println!("  Show data: {}", pie_chart.show_data);
+            if let Some(title) = &pie_chart.title {
+                println!("  Title: {}", title);
+            }
+            println!("  Data entries: {}", pie_chart.data.len());
+            for entry in &pie_chart.data {
+                println!("    \"{}\": {}", entry.label, entry.value);
+            }
+        }
+        Chart::XY(xychart) => {
+            println!("\nParsed XY chart:");
+            if let Some(title) = &xychart.title {
+                println!("  Title: {}", title);
+            }
+            println!("  X-axis labels: {:?}", xychart.x_axis.labels);
+            println!(
+                "  Y-axis: \"{}\" {} -> {}",
+                xychart.y_axis.title, xychart.y_axis.min, xychart.y_axis.max
+            );
+            if let Some(right_axis) = &xychart.y_axis_right {
+                println!(
+                    "  Right Y-axis: \"{}\" {} -> {}",
+                    right_axis.title, right_axis.min, right_axis.max
+                );
+            }
+            println!("  Series count: {}", xychart.series.len());
+            println!("  Annotation count: {}", xychart.annotations.len());
+            for (i, series) in xychart.series.iter().enumerate() {
+                println!(
+                    "    Series {}: {:?} ({:?} axis) {:?}",
+                    i, series.series_type, series.axis, series.data
+                );
+            }
+        }
+        Chart::WorkItemMovement(work_item_movement) => {
+            println!("\nParsed work item movement chart:");
+            if let Some(title) = &work_item_movement.title {
+                println!("  Title: {}", title);
+            }
+            println!("  Columns: {:?}", work_item_movement.columns);
+            println!("  Work items: {}", work_item_movement.items.len());
+            for item in &work_item_movement.items {
+                let title = item
+                    .title
+                    .as_ref()
+                    .map(|title| format!(" \"{}\"", title))
+                    .unwrap_or_default();
+                let history: Vec<String> = item
+                    .states
+                    .iter()
+                    .map(|state| format!("{} ({})", state.name, state.points))
+                    .collect();
+                println!("    {}{}: {}", item.id, title, history.join(" -> "));
+            }
+        }
+    }
+}
+
+/// Build a pie or xy chart from a CSV or JSON data file
+fn run_data(cli: &Cli, data: &str, output: &str, output_format: &str) {
+    let Some(data_format) = DataFormat::from_path(data) else {
+        eprintln!(
+            "Error: Could not detect the data format of '{}'. Supported formats: csv, json",
+            data
+        );
+        std::process::exit(1);
+    };
+    let table = match fs::read_to_string(data)
+        .map_err(|e| e.to_string())
+        .and_then(|content| DataTable::parse(&content, data_format).map_err(|e| e.to_string()))
+    {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Failed to read data file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if cli.verbose {
+        println!("\nData columns: {:?}", table.columns);
+        println!("Data rows: {}", table.rows.len());
+    }
+
+    match cli.chart_type.as_deref() {
+        Some("pie") => {
+            let spec = PieDataSpec {
+                label_column: cli.label_column.clone(),
+                value_column: cli.value_column.clone(),
+                title: cli.title.clone(),
+                show_data: cli.show_data,
+            };
+            let pie_chart = match pie_chart_from_data(&table, &spec) {
+                Ok(pie_chart) => pie_chart,
+                Err(e) => {
+                    eprintln!("Failed to build pie chart: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            write_output(&Chart::Pie(pie_chart), &cli.render, output, output_format);
+        }
+        _ => {
+            let spec = XYDataSpec {
+                x_column: cli.x_column.clone(),
+                series: cli.series.iter().map(|s| s.trim().to_string()).collect(),
+                series_type: match cli.series_type.as_str() {
+                    "line" => SeriesType::Line,
+                    "area" => SeriesType::Area,
+                    "stacked-area" => SeriesType::StackedArea,
+                    _ => SeriesType::Bar,
+                },
+                title: cli.title.clone(),
+                ..Default::default()
+            };
+            let xychart = match xychart_from_data(&table, &spec) {
+                Ok(xychart) => xychart,
+                Err(e) => {
+                    eprintln!("Failed to build XY chart: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            write_output(&Chart::XY(xychart), &cli.render, output, output_format);
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
+    match cli.command {
+        Some(Commands::Import { source }) => {
+            run_import(source);
+            return;
+        }
+        Some(Commands::Markdown {
+            input,
+            output,
+            format,
+            render,
+        }) => {
+            run_markdown(&input, &output, &format, &render);
+            return;
+        }
+        Some(Commands::Render {
+            patterns,
+            out_dir,
+            format,
+            jobs,
+            render,
+        }) => {
+            run_render(&patterns, &out_dir, &format, jobs, &render);
+            return;
+        }
+        None => {}
+    }
+    // The output is required when there is no subcommand
+    let Some(output) = cli.output.clone() else {
+        unreachable!("clap requires the output");
+    };
+
     println!("Pisnge - Diagram Renderer");
 
     // Determine output format: use -f flag if provided, otherwise detect from file extension
-    let output_format = match cli.format {
+    let output_format = match cli.format.clone() {
         Some(format) => format,
-        None => match detect_format_from_extension(&cli.output) {
+        None => match detect_format_from_extension(&output) {
             Some(format) => format,
             None => {
                 eprintln!("Error: Could not detect output format from file extension '{}'. Please specify format using -f flag.", 
-                    Path::new(&cli.output).extension().and_then(|ext| ext.to_str()).unwrap_or("(none)"));
-                eprintln!("Supported formats: png, svg");
+                    Path::new(&output).extension().and_then(|ext| ext.to_str()).unwrap_or("(none)"));
+                eprintln!("Supported formats: png, svg, mmd");
                 std::process::exit(1);
             }
         },
     };
 
+    if let Some(data) = &cli.data {
+        if cli.verbose {
+            println!("Data file: {}", data);
+            println!("Output file: {}", output);
+            println!("Output format: {}", output_format);
+        }
+        run_data(&cli, data, &output, &output_format);
+        return;
+    }
+    // The input is required without a data file
+    let Some(input) = cli.input.clone() else {
+        unreachable!("clap requires the input");
+    };
+
     if cli.verbose {
-        println!("Input file: {}", cli.input);
-        println!("Output file: {}", cli.output);
+        println!("Input file: {}", input);
+        println!("Output file: {}", output);
         println!("Output format: {}", output_format);
     }
 
-    match fs::read_to_string(&cli.input) {
-        Ok(content) => {
-            // Ensure content ends with newline for easier parsing
-            let normalized_content = if content.ends_with('\n') {
-                content
-            } else {
-                format!("{}\n", content)
-            };
-
-            // First parse config and detect chart type
-            match parse_config_and_detect_type(&normalized_content) {
-                Ok((_, (config, chart_type, remaining_content))) => {
-                    if cli.verbose {
-                        println!("\nDetected chart type: {:?}", chart_type);
-                        if let Some(ref config) = config {
-                            println!("Theme: {}", config.theme);
-                            if !config.theme_variables.is_empty() {
-                                println!("Theme variables: {:?}", config.theme_variables);
-                            }
-                        }
-                    }
-
-                    match chart_type {
-                        ChartType::Pie => {
-                            match parse_pie_chart_content(remaining_content, config) {
-                                Ok((_, pie_chart)) => {
-                                    if cli.verbose {
-                                        println!("\nParsed pie chart:");
-                                        println!("  Show data: {}", pie_chart.show_data);
-                                        if let Some(title) = &pie_chart.title {
-                                            println!("  Title: {}", title);
-                                        }
-                                        println!("  Data entries: {}", pie_chart.data.len());
-                                        for entry in &pie_chart.data {
-                                            println!("    \"{}\": {}", entry.label, entry.value);
-                                        }
-                                    }
-
-                                    match output_format.as_str() {
-                                        "svg" => {
-                                            let (svg_document, _, _) = render_pie_chart_svg(
-                                                &pie_chart, cli.width, cli.height, &cli.font,
-                                            );
-                                            match fs::write(&cli.output, svg_document.to_string()) {
-                                                Ok(_) => println!("SVG saved to: {}", cli.output),
-                                                Err(e) => {
-                                                    eprintln!("Failed to write SVG file: {}", e);
-                                                    std::process::exit(1);
-                                                }
-                                            }
-                                        }
-                                        "png" => {
-                                            let (svg_document, actual_width, actual_height) =
-                                                render_pie_chart_svg(
-                                                    &pie_chart, cli.width, cli.height, &cli.font,
-                                                );
-                                            match svg_to_png(
-                                                &svg_document.to_string(),
-                                                actual_width,
-                                                actual_height,
-                                                &cli.font,
-                                            ) {
-                                                Ok(png_data) => {
-                                                    match fs::write(&cli.output, png_data) {
-                                                        Ok(_) => {
-                                                            println!("PNG saved to: {}", cli.output)
-                                                        }
-                                                        Err(e) => {
-                                                            eprintln!(
-                                                                "Failed to write PNG file: {}",
-                                                                e
-                                                            );
-                                                            std::process::exit(1);
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    eprintln!(
-                                                        "Failed to convert SVG to PNG: {}",
-                                                        e
-                                                    );
-                                                    std::process::exit(1);
-                                                }
-                                            }
-                                        }
-                                        _ => {
-                                            eprintln!(
-                                                "Unsupported format: {}. Supported formats: png, svg",
-                                                output_format
-                                            );
-                                            std::process::exit(1);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse pie chart: {:?}", e);
-                                    std::process::exit(1);
-                                }
-                            }
-                        }
-                        ChartType::XY => match parse_xychart_content(remaining_content, config) {
-                            Ok((_, xychart)) => {
-                                if cli.verbose {
-                                    println!("\nParsed XY chart:");
-                                    if let Some(title) = &xychart.title {
-                                        println!("  Title: {}", title);
-                                    }
-                                    println!("  X-axis labels: {:?}", xychart.x_axis.labels);
-                                    println!(
-                                        "  Y-axis: \"{}\" {} -> {}",
-                                        xychart.y_axis.title,
-                                        xychart.y_axis.min,
-                                        xychart.y_axis.max
-                                    );
-                                    println!("  Series count: {}", xychart.series.len());
-                                    for (i, series) in xychart.series.iter().enumerate() {
-                                        println!(
-                                            "    Series {}: {:?} {:?}",
-                                            i, series.series_type, series.data
-                                        );
-                                    }
-                                }
-
-                                match output_format.as_str() {
-                                    "svg" => {
-                                        let (svg_document, _, _) = render_xychart_svg(
-                                            &xychart, cli.width, cli.height, &cli.font,
-                                        );
-                                        match fs::write(&cli.output, svg_document.to_string()) {
-                                            Ok(_) => println!("SVG saved to: {}", cli.output),
-                                            Err(e) => {
-                                                eprintln!("Failed to write SVG file: {}", e);
-                                                std::process::exit(1);
-                                            }
-                                        }
-                                    }
-                                    "png" => {
-                                        let (svg_document, actual_width, actual_height) =
-                                            render_xychart_svg(
-                                                &xychart, cli.width, cli.height, &cli.font,
-                                            );
-                                        match svg_to_png(
-                                            &svg_document.to_string(),
-                                            actual_width,
-                                            actual_height,
-                                            &cli.font,
-                                        ) {
-                                            Ok(png_data) => {
-                                                match fs::write(&cli.output, png_data) {
-                                                    Ok(_) => {
-                                                        println!("PNG saved to: {}", cli.output)
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!(
-                                                            "Failed to write PNG file: {}",
-                                                            e
-                                                        );
-                                                        std::process::exit(1);
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("Failed to convert SVG to PNG: {}", e);
-                                                std::process::exit(1);
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        eprintln!(
-                                            "Unsupported format: {}. Supported formats: png, svg",
-                                            output_format
-                                        );
-                                        std::process::exit(1);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to parse XY chart: {:?}", e);
-                                std::process::exit(1);
-                            }
-                        },
-                        ChartType::WorkItemMovement => {
-                            match parse_work_item_movement(remaining_content, config) {
-                                Ok((_, work_item_movement)) => {
-                                    // Validate the work item movement chart
-                                    if let Err(e) = validate_work_item_movement(&work_item_movement)
-                                    {
-                                        eprintln!("Error: {}", e);
-                                        std::process::exit(1);
-                                    }
-                                    if cli.verbose {
-                                        println!("\nParsed work item movement chart:");
-                                        if let Some(title) = &work_item_movement.title {
-                                            println!("  Title: {}", title);
-                                        }
-                                        println!("  Columns: {:?}", work_item_movement.columns);
-                                        println!(
-                                            "  Work items: {}",
-                                            work_item_movement.items.len()
-                                        );
-                                        for item in &work_item_movement.items {
-                                            println!(
-                                                "    {}: {} ({}) -> {} ({})",
-                                                item.id,
-                                                item.from_state,
-                                                item.from_points,
-                                                item.to_state,
-                                                item.to_points
-                                            );
-                                        }
-                                    }
-
-                                    match output_format.as_str() {
-                                        "svg" => {
-                                            let (svg_document, _, _) =
-                                                render_work_item_movement_svg(
-                                                    &work_item_movement,
-                                                    cli.width,
-                                                    &cli.font,
-                                                );
-                                            match fs::write(&cli.output, svg_document.to_string()) {
-                                                Ok(_) => println!("SVG saved to: {}", cli.output),
-                                                Err(e) => {
-                                                    eprintln!("Failed to write SVG file: {}", e);
-                                                    std::process::exit(1);
-                                                }
-                                            }
-                                        }
-                                        "png" => {
-                                            let (svg_document, actual_width, actual_height) =
-                                                render_work_item_movement_svg(
-                                                    &work_item_movement,
-                                                    cli.width,
-                                                    &cli.font,
-                                                );
-                                            match svg_to_png(
-                                                &svg_document.to_string(),
-                                                actual_width,
-                                                actual_height,
-                                                &cli.font,
-                                            ) {
-                                                Ok(png_data) => {
-                                                    match fs::write(&cli.output, png_data) {
-                                                        Ok(_) => {
-                                                            println!("PNG saved to: {}", cli.output)
-                                                        }
-                                                        Err(e) => {
-                                                            eprintln!(
-                                                                "Failed to write PNG file: {}",
-                                                                e
-                                                            );
-                                                            std::process::exit(1);
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    eprintln!(
-                                                        "Failed to convert SVG to PNG: {}",
-                                                        e
-                                                    );
-                                                    std::process::exit(1);
-                                                }
-                                            }
-                                        }
-                                        _ => {
-                                            eprintln!(
-                                                "Unsupported format: {}. Supported formats: png, svg",
-                                                output_format
-                                            );
-                                            std::process::exit(1);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to parse work item movement chart: {:?}", e);
-                                    std::process::exit(1);
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Failed to parse chart (unknown type or invalid config): {:?}",
-                        e
-                    );
-                    std::process::exit(1);
-                }
-            }
-        }
+    let chart = match Chart::from_file(Path::new(&input)) {
+        Ok(chart) => chart,
         Err(e) => {
-            eprintln!("Failed to read input file: {}", e);
+            eprintln!("{}", e);
             std::process::exit(1);
         }
+    };
+    if cli.verbose {
+        print_chart_details(&chart);
     }
+
+    write_output(&chart, &cli.render, &output, &output_format);
 }
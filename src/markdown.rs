@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+/// A fenced code block with the `mermaid` info string
+#[derive(Debug, Clone, PartialEq)]
+pub struct MermaidBlock {
+    /// The bytes of the block from the start of its opening fence to the end of its closing fence
+    pub range: Range<usize>,
+    /// The indentation of the opening fence, which is removed from the content
+    pub indent: usize,
+    pub content: String,
+}
+
+struct Fence<'a> {
+    indent: usize,
+    fence_char: char,
+    length: usize,
+    info: &'a str,
+}
+
+/// A line opening a code block with up to three spaces of indentation and at least three
+/// backticks or tildes
+fn opening_fence(line: &str) -> Option<Fence<'_>> {
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    if indent > 3 {
+        return None;
+    }
+
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == fence_char).count();
+    let info = trimmed[length..].trim();
+    // Backticks in the info string would make the line an inline code span
+    if length < 3 || (fence_char == '`' && info.contains('`')) {
+        return None;
+    }
+
+    Some(Fence {
+        indent,
+        fence_char,
+        length,
+        info,
+    })
+}
+
+fn is_closing_fence(line: &str, fence: &Fence) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    let length = trimmed
+        .chars()
+        .take_while(|c| *c == fence.fence_char)
+        .count();
+    line.len() - trimmed.len() <= 3 && length >= fence.length && trimmed[length..].trim().is_empty()
+}
+
+/// Remove up to `indent` spaces from the start of a line
+fn remove_indent(line: &str, indent: usize) -> &str {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    &line[spaces.min(indent)..]
+}
+
+/// Find the mermaid code blocks of a markdown document. Code blocks of other languages are
+/// skipped so mermaid examples inside them are left alone, and a block without a closing fence
+/// runs to the end of the document.
+pub fn find_mermaid_blocks(markdown: &str) -> Vec<MermaidBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    });
+
+    while let Some((start, line)) = lines.next() {
+        let Some(fence) = opening_fence(line) else {
+            continue;
+        };
+
+        let mut content = String::new();
+        let mut end = markdown.len();
+        for (line_start, line) in lines.by_ref() {
+            if is_closing_fence(line, &fence) {
+                end = line_start + line.len();
+                break;
+            }
+            content.push_str(remove_indent(line, fence.indent));
+        }
+
+        if fence.info.split_whitespace().next() == Some("mermaid") {
+            blocks.push(MermaidBlock {
+                range: start..end,
+                indent: fence.indent,
+                content,
+            });
+        }
+    }
+    blocks
+}
+
+/// Replace ranges of the document, which must be in order and not overlap
+pub fn replace_ranges(markdown: &str, replacements: &[(Range<usize>, String)]) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut position = 0;
+    for (range, replacement) in replacements {
+        result.push_str(&markdown[position..range.start]);
+        result.push_str(replacement);
+        position = range.end;
+    }
+    result.push_str(&markdown[position..]);
+    result
+}
+
+/// A markdown image, escaping the characters that would end the alt text or link early
+pub fn image_link(alt: &str, path: &str) -> String {
+    let alt = alt
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    let path = path
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29");
+    format!("![{}]({})", alt, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_mermaid_blocks() {
+        let markdown = r#"# Charts
+
+```mermaid
+pie
+  "Done": 3
+```
+
+~~~~ mermaid title
+xychart-beta
+```
+~~~~
+
+```rust
+println!("```mermaid");
+```
+
+````markdown
+```mermaid
+pie
+```
+````
+
+- A list item
+
+   ```mermaid
+   work-item-movement
+     columns [To Do, Done]
+   ```
+
+```mermaid
+flowchart LR
+"#;
+
+        let blocks = find_mermaid_blocks(markdown);
+        let contents: Vec<&str> = blocks.iter().map(|block| block.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "pie\n  \"Done\": 3\n",
+                "xychart-beta\n```\n",
+                "work-item-movement\n  columns [To Do, Done]\n",
+                "flowchart LR\n",
+            ]
+        );
+        assert_eq!(
+            &markdown[blocks[0].range.clone()],
+            "```mermaid\npie\n  \"Done\": 3\n```\n"
+        );
+        assert_eq!(blocks[2].indent, 3);
+        assert_eq!(blocks[3].range.end, markdown.len());
+    }
+
+    #[test]
+    fn test_replace_ranges() {
+        let markdown = "Before\n```mermaid\npie\n```\nAfter\n";
+        let blocks = find_mermaid_blocks(markdown);
+        let replaced = replace_ranges(
+            markdown,
+            &[(
+                blocks[0].range.clone(),
+                image_link("Points", "README-1.svg") + "\n",
+            )],
+        );
+        assert_eq!(replaced, "Before\n![Points](README-1.svg)\nAfter\n");
+    }
+
+    #[test]
+    fn test_image_link() {
+        assert_eq!(
+            image_link("Points [Q1]", "my docs (1).png"),
+            "![Points \\[Q1\\]](my%20docs%20%281%29.png)"
+        );
+    }
+}
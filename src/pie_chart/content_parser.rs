@@ -8,7 +8,7 @@ use nom::{
 };
 
 use super::{PieChart, PieChartData};
-use crate::common::{number, quoted_string, ChartConfig};
+use crate::common::{number, string_parser::any_quoted_string, ChartConfig};
 
 fn pie_header(input: &str) -> IResult<&str, (bool, Option<String>)> {
     let (input, _) = tag("pie")(input)?;
@@ -24,18 +24,12 @@ fn pie_header(input: &str) -> IResult<&str, (bool, Option<String>)> {
 
 fn pie_data_entry(input: &str) -> IResult<&str, PieChartData> {
     let (input, _) = multispace0(input)?;
-    let (input, label) = quoted_string(input)?;
+    let (input, label) = any_quoted_string(input)?;
     let (input, _) = tag(":")(input)?;
     let (input, _) = space0(input)?;
     let (input, value) = number(input)?;
 
-    Ok((
-        input,
-        PieChartData {
-            label: label.to_string(),
-            value,
-        },
-    ))
+    Ok((input, PieChartData { label, value }))
 }
 
 pub fn parse_pie_chart_content(
@@ -0,0 +1,92 @@
+use super::{PieChart, PieChartData};
+use crate::common::data::{DataError, DataTable};
+
+/// The columns of a data table that a pie chart is built from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PieDataSpec {
+    /// The column of slice labels, defaults to the first column
+    pub label_column: Option<String>,
+    /// The column of slice values, defaults to the second column
+    pub value_column: Option<String>,
+    pub title: Option<String>,
+    pub show_data: bool,
+}
+
+/// Build a pie chart with a slice for each row of the table
+pub fn pie_chart_from_data(table: &DataTable, spec: &PieDataSpec) -> Result<PieChart, DataError> {
+    let column = |name: &Option<String>, index: usize| match name {
+        Some(name) => Ok(name.clone()),
+        None => table.columns.get(index).cloned().ok_or_else(|| DataError {
+            message: "Pie chart data needs a label column and a value column".to_string(),
+        }),
+    };
+    let label_column = column(&spec.label_column, 0)?;
+    let value_column = column(&spec.value_column, 1)?;
+
+    let labels = table.text_column(&label_column)?;
+    let values = table.number_column(&value_column)?;
+    let data = labels
+        .into_iter()
+        .zip(values)
+        .map(|(label, value)| {
+            value
+                .map(|value| PieChartData {
+                    label: label.clone(),
+                    value,
+                })
+                .ok_or_else(|| DataError {
+                    message: format!(
+                        "Slice '{}' has no value in column '{}'",
+                        label, value_column
+                    ),
+                })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(PieChart {
+        config: None,
+        show_data: spec.show_data,
+        title: spec.title.clone(),
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pie_chart_from_data() {
+        let table =
+            DataTable::from_csv("status,points,issues\nDone,262,40\n\"To Do, later\",129,12\n")
+                .unwrap();
+
+        let pie_chart = pie_chart_from_data(&table, &PieDataSpec::default()).unwrap();
+        assert_eq!(
+            pie_chart.data,
+            vec![
+                PieChartData {
+                    label: "Done".to_string(),
+                    value: 262.0
+                },
+                PieChartData {
+                    label: "To Do, later".to_string(),
+                    value: 129.0
+                },
+            ]
+        );
+
+        let pie_chart = pie_chart_from_data(
+            &table,
+            &PieDataSpec {
+                value_column: Some("issues".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(pie_chart.data[1].value, 12.0);
+
+        let table = DataTable::from_csv("status,points\nDone,\n").unwrap();
+        assert!(pie_chart_from_data(&table, &PieDataSpec::default()).is_err());
+    }
+}
@@ -4,23 +4,31 @@ use crate::common::ChartConfig;
 pub type PieChartConfig = ChartConfig;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct PieChartData {
     pub label: String,
     pub value: f64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct PieChart {
     pub config: Option<PieChartConfig>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub show_data: bool,
     pub title: Option<String>,
     pub data: Vec<PieChartData>,
 }
 
 pub mod content_parser;
+pub mod data;
 pub mod parser;
 pub mod renderer;
+pub mod writer;
 
 pub use content_parser::*;
+pub use data::*;
 pub use parser::*;
 pub use renderer::*;
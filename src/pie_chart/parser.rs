@@ -7,7 +7,7 @@ use nom::{
     IResult,
 };
 
-use crate::common::{config_line, number, quoted_string};
+use crate::common::{config_line, number, string_parser::any_quoted_string};
 use crate::{PieChart, PieChartData};
 
 fn pie_header(input: &str) -> IResult<&str, (bool, Option<String>)> {
@@ -24,18 +24,12 @@ fn pie_header(input: &str) -> IResult<&str, (bool, Option<String>)> {
 
 fn pie_data_entry(input: &str) -> IResult<&str, PieChartData> {
     let (input, _) = multispace0(input)?;
-    let (input, label) = quoted_string(input)?;
+    let (input, label) = any_quoted_string(input)?;
     let (input, _) = tag(":")(input)?;
     let (input, _) = space0(input)?;
     let (input, value) = number(input)?;
 
-    Ok((
-        input,
-        PieChartData {
-            label: label.to_string(),
-            value,
-        },
-    ))
+    Ok((input, PieChartData { label, value }))
 }
 
 pub fn parse_pie_chart(input: &str) -> IResult<&str, PieChart> {
@@ -88,10 +82,30 @@ pie showData title Story points by status
             config.theme_variables.get("pie1"),
             Some(&"blue".to_string())
         );
-        assert_eq!(pie_chart.show_data, true);
+        assert!(pie_chart.show_data);
         assert_eq!(pie_chart.title, Some("Story points by status".to_string()));
         assert_eq!(pie_chart.data.len(), 6);
         assert_eq!(pie_chart.data[0].label, "Done");
         assert_eq!(pie_chart.data[0].value, 262.0);
     }
+
+    #[test]
+    fn test_parse_pie_chart_with_braces_in_theme_variable() {
+        let input = r#"%%{init: {'themeVariables': {'pieInnerRadius': '0.5', 'pieCenterText': '{total} points'}}}%%
+pie
+  "Done": 262
+  "To Do": 129
+"#;
+
+        let (_, pie_chart) = parse_pie_chart(input).unwrap();
+        let config = pie_chart.config.as_ref().unwrap();
+        assert_eq!(
+            config.theme_variables.get("pieCenterText"),
+            Some(&"{total} points".to_string())
+        );
+        assert_eq!(
+            config.theme_variables.get("pieInnerRadius"),
+            Some(&"0.5".to_string())
+        );
+    }
 }
@@ -1,7 +1,11 @@
+use crate::common::format::{format_template, TemplateValue};
+use crate::common::renderer::{
+    layout_legend, parse_legend_position, render_legend, LegendConfig, LegendPosition,
+};
 use crate::font::{load_system_font_bytes, measure_text_height, measure_text_width};
-use crate::PieChart;
+use crate::{PieChart, PieChartData};
 use std::f64::consts::PI;
-use svg::node::element::{Circle, Group, Path, Rectangle, Style, Text};
+use svg::node::element::{Circle, Group, Path, Style, Text};
 use svg::Document;
 
 const DEFAULT_COLORS: [&str; 10] = [
@@ -9,6 +13,15 @@ const DEFAULT_COLORS: [&str; 10] = [
     "#bcbd22", "#17becf",
 ];
 
+/// Distance from the edge of the chart to where a leader line bends towards its label
+const LEADER_LINE_ELBOW: f64 = 10.0;
+/// Length of the horizontal part of a leader line
+const LEADER_LINE_LENGTH: f64 = 15.0;
+/// Gap between the end of a leader line and its label
+const LEADER_LINE_TEXT_GAP: f64 = 4.0;
+/// Horizontal space needed beyond the edge of the chart for an outside label, excluding its text
+const OUTSIDE_LABEL_OFFSET: f64 = LEADER_LINE_ELBOW + LEADER_LINE_LENGTH + LEADER_LINE_TEXT_GAP;
+
 pub fn render_pie_chart_svg(
     pie_chart: &PieChart,
     default_width: u32,
@@ -24,8 +37,8 @@ pub fn render_pie_chart_svg(
     // Load font data once for both title and legend calculations
     let font_data = load_system_font_bytes(font_name);
 
-    // Calculate the actual legend width needed
-    let legend_width = calculate_legend_width(pie_chart, &font_data);
+    // The slices to draw after sorting and merging small slices
+    let slices = prepare_slices(pie_chart);
 
     // Calculate title height and spacing
     let (title_height, title_to_chart_gap) = if pie_chart.title.is_some() {
@@ -48,37 +61,152 @@ pub fn render_pie_chart_svg(
     let vertical_margin = 35.0; // Equal top and bottom margin
     let side_margin = 30.0; // Equal left and right margin
     let chart_to_legend_gap = 20.0; // Gap between chart and legend
+    let available_content_height =
+        height as f64 - (vertical_margin * 2.0) - title_height - title_to_chart_gap;
+
+    // Percentages are rounded so that they add up to 100 and shared by the legend and labels
+    let total: f64 = slices.iter().map(|d| d.value).sum();
+    let decimals = get_label_decimals(pie_chart);
+    let percentages = largest_remainder_percentages(&slices, total, decimals);
+
+    // Lay out the legend, legends beside the chart wrap into columns when they are taller than
+    // the available height and legends above or below it wrap into rows
+    let legend_template = get_theme_variable(pie_chart, "pieLegendTemplate", "{label} [{value}]");
+    let legend_labels: Vec<String> = slices
+        .iter()
+        .zip(&percentages)
+        .map(|(data, percent)| format_slice_template(legend_template, data, *percent, decimals))
+        .collect();
+    let legend_colors: Vec<String> = (0..slices.len())
+        .map(|i| get_color_for_slice(pie_chart, i).to_string())
+        .collect();
+    let legend_config = LegendConfig {
+        font_name: font_name.to_string(),
+        font_size: parse_font_size(
+            get_theme_variable(pie_chart, "pieLegendTextSize", "17px"),
+            17.0,
+        ) as f64,
+        border_color: get_theme_variable(pie_chart, "pieStrokeColor", "black").to_string(),
+        icon_opacity: get_theme_variable(pie_chart, "pieOpacity", "0.7")
+            .parse()
+            .unwrap_or(0.7),
+        ..Default::default()
+    };
+    let legend_position =
+        parse_legend_position(get_theme_variable(pie_chart, "legendPosition", "right"));
+    let legend_layout = layout_legend(
+        &legend_labels,
+        &font_data,
+        &legend_config,
+        legend_position,
+        width as f64 - (side_margin * 2.0),
+        available_content_height,
+    );
+    let legend_position = legend_layout.position;
+    // The horizontal and vertical space taken up by the legend
+    let (legend_space_x, legend_space_y) = if legend_layout.rows == 0 {
+        (0.0, 0.0)
+    } else if legend_position.is_beside() {
+        (legend_layout.width + chart_to_legend_gap, 0.0)
+    } else {
+        (0.0, legend_layout.height + chart_to_legend_gap)
+    };
+    let legend_height = if legend_position.is_beside() {
+        legend_layout.height
+    } else {
+        0.0
+    };
 
     // Calculate available space for the pie chart (width-constrained)
-    let available_chart_width =
-        width as f64 - (side_margin * 2.0) - legend_width - chart_to_legend_gap;
-
-    // Calculate optimal radius based on width only (let height grow as needed)
-    let radius = (available_chart_width / 2.0) * 0.9;
-
-    // Calculate the actual height needed based on optimized content
-    let legend_height = pie_chart.data.len() as f64 * 22.0; // 22px per legend item
-    let chart_diameter = radius * 2.0;
-    let content_height = chart_diameter.max(legend_height);
-    let optimal_height = vertical_margin * 2.0 + title_height + title_to_chart_gap + content_height;
-
-    // If optimal height exceeds CLI height, apply height constraint
-    let (final_radius, actual_height) = if optimal_height > height as f64 {
-        let available_chart_height =
-            height as f64 - (vertical_margin * 2.0) - title_height - title_to_chart_gap;
-        let constrained_radius =
-            ((available_chart_width / 2.0).min(available_chart_height / 2.0)) * 0.9;
-        (constrained_radius, height as f64)
+    let available_chart_width = width as f64 - (side_margin * 2.0) - legend_space_x;
+
+    // The inner radius is a fraction of the outer radius, anything above zero draws a donut
+    let inner_radius_str = get_theme_variable(pie_chart, "pieInnerRadius", "0");
+    let inner_radius_fraction = match inner_radius_str.parse::<f64>() {
+        Ok(fraction) if (0.0..1.0).contains(&fraction) => fraction,
+        _ => {
+            eprintln!(
+                "Warning: Invalid pie inner radius '{}', must be a fraction from 0 to 1",
+                inner_radius_str
+            );
+            0.0
+        }
+    };
+
+    // Measure the percentage labels, labels that don't fit inside their slice are drawn
+    // outside of the chart so space is reserved for them on each side
+    let slice_labels = if pie_chart.show_data {
+        measure_slice_labels(pie_chart, &slices, &percentages, decimals, &font_data)
     } else {
-        (radius, optimal_height)
+        Vec::new()
+    };
+    let slice_angles: Vec<f64> = slices
+        .iter()
+        .map(|d| (d.value / total) * 2.0 * PI)
+        .collect();
+    let outside_label_space = |radius: f64| {
+        slice_labels
+            .iter()
+            .zip(&slice_angles)
+            .filter(|(label, angle)| {
+                !slice_label_fits(label, **angle, radius, radius * inner_radius_fraction)
+            })
+            .map(|(label, _)| label.width + OUTSIDE_LABEL_OFFSET)
+            .fold(0.0, f64::max)
     };
 
-    // Position elements
-    let center_x = side_margin + available_chart_width / 2.0;
-    let final_content_height = (final_radius * 2.0).max(legend_height);
-    let center_y = vertical_margin + title_height + title_to_chart_gap + final_content_height / 2.0;
+    let fit_radius = |label_space: f64| {
+        // Calculate optimal radius based on width only (let height grow as needed)
+        let radius = ((available_chart_width / 2.0 - label_space) * 0.9).max(1.0);
+
+        // Calculate the actual height needed based on optimized content
+        let chart_diameter = radius * 2.0;
+        let content_height = (chart_diameter + legend_space_y).max(legend_height);
+        let optimal_height =
+            vertical_margin * 2.0 + title_height + title_to_chart_gap + content_height;
+
+        // If optimal height exceeds CLI height, apply height constraint
+        if optimal_height > height as f64 {
+            let available_chart_height = available_content_height - legend_space_y;
+            let constrained_radius = radius.min((available_chart_height / 2.0) * 0.9);
+            (constrained_radius, height as f64)
+        } else {
+            (radius, optimal_height)
+        }
+    };
 
-    let total: f64 = pie_chart.data.iter().map(|d| d.value).sum();
+    // Shrinking the chart to make space for outside labels can push more labels outside, so
+    // repeat until the space reserved for them is enough
+    let mut label_space = 0.0;
+    let (final_radius, actual_height) = loop {
+        let (radius, actual_height) = fit_radius(label_space);
+        let needed_space = outside_label_space(radius);
+        if needed_space <= label_space {
+            break (radius, actual_height);
+        }
+        label_space = needed_space;
+    };
+    let inner_radius = final_radius * inner_radius_fraction;
+
+    // Position elements, a legend above the chart moves the chart and its title down
+    let chart_left = if legend_position == LegendPosition::Left {
+        side_margin + legend_space_x
+    } else {
+        side_margin
+    };
+    let center_x = chart_left + available_chart_width / 2.0;
+    let content_top = vertical_margin + title_height + title_to_chart_gap;
+    let final_content_height = (final_radius * 2.0 + legend_space_y).max(legend_height);
+    let center_y = match legend_position {
+        LegendPosition::Top => content_top + legend_space_y + final_radius,
+        LegendPosition::Bottom => content_top + final_radius,
+        _ => content_top + final_content_height / 2.0,
+    };
+    let legend_above_height = if legend_position == LegendPosition::Top {
+        legend_space_y
+    } else {
+        0.0
+    };
 
     let mut document = Document::new()
         .set("viewBox", (0, 0, width, actual_height as u32))
@@ -102,14 +230,18 @@ pub fn render_pie_chart_svg(
     let pie_section_text_size = get_theme_variable(pie_chart, "pieSectionTextSize", "17px");
     let pie_legend_text_size = get_theme_variable(pie_chart, "pieLegendTextSize", "17px");
     let pie_legend_text_color = get_theme_variable(pie_chart, "pieLegendTextColor", "black");
+    let pie_center_text_size = get_theme_variable(pie_chart, "pieCenterTextSize", "25px");
 
-    let style = Style::new(&format!(
+    let style = Style::new(format!(
         r#"
             .pieCircle {{ stroke: {}; stroke-width: {}; fill-opacity: {}; }}
             .pieOuterCircle {{ stroke: {}; stroke-width: {}; fill: none; }}
             .pieTitleText {{ text-anchor: middle; font-size: {}; fill: {}; font-family: "{}", sans-serif; }}
             .slice {{ font-family: "{}", sans-serif; fill: {}; font-size: {}; text-anchor: middle; }}
+            .sliceOutside {{ font-family: "{}", sans-serif; fill: {}; font-size: {}; }}
+            .pieLeaderLine {{ stroke: {}; stroke-width: 1px; fill: none; }}
             .legend text {{ fill: {}; font-family: "{}", sans-serif; font-size: {}; }}
+            .pieCenterText {{ text-anchor: middle; font-size: {}; fill: {}; font-family: "{}", sans-serif; }}
         "#,
         pie_stroke_color,
         pie_stroke_width,
@@ -122,9 +254,16 @@ pub fn render_pie_chart_svg(
         font_name,
         pie_section_text_color,
         pie_section_text_size,
+        font_name,
+        pie_section_text_color,
+        pie_section_text_size,
+        pie_section_text_color,
         pie_legend_text_color,
         font_name,
-        pie_legend_text_size
+        pie_legend_text_size,
+        pie_center_text_size,
+        pie_title_text_color,
+        font_name
     ));
 
     document = document.add(style);
@@ -134,13 +273,24 @@ pub fn render_pie_chart_svg(
 
     let mut current_angle = -PI / 2.0;
 
-    for (i, data) in pie_chart.data.iter().enumerate() {
+    for (i, data) in slices.iter().enumerate() {
         let slice_angle = (data.value / total) * 2.0 * PI;
         let end_angle = current_angle + slice_angle;
 
         let color = get_color_for_slice(pie_chart, i);
 
-        let path_data = create_pie_slice_path(0.0, 0.0, final_radius, current_angle, end_angle);
+        let path_data = if inner_radius > 0.0 {
+            create_donut_slice_path(
+                0.0,
+                0.0,
+                final_radius,
+                inner_radius,
+                current_angle,
+                end_angle,
+            )
+        } else {
+            create_pie_slice_path(0.0, 0.0, final_radius, current_angle, end_angle)
+        };
 
         main_group = main_group.add(
             Path::new()
@@ -149,31 +299,28 @@ pub fn render_pie_chart_svg(
                 .set("d", path_data),
         );
 
-        if pie_chart.show_data {
-            let mid_angle = current_angle + slice_angle / 2.0;
-            let label_radius = final_radius * 0.75;
-            let label_x = label_radius * mid_angle.cos();
-            let label_y = label_radius * mid_angle.sin();
-
-            let percentage = ((data.value / total) * 100.0).round();
+        current_angle = end_angle;
+    }
 
-            let section_font_size = parse_font_size(
-                get_theme_variable(pie_chart, "pieSectionTextSize", "17px"),
-                17.0,
-            );
+    for label in layout_slice_labels(&slice_labels, &slice_angles, final_radius, inner_radius) {
+        if let Some(leader_line) = label.leader_line {
             main_group = main_group.add(
-                Text::new(format!("{}%", percentage))
-                    .set("class", "slice")
-                    .set("x", label_x)
-                    .set("y", label_y)
-                    .set("font-family", format!("{}, sans-serif", font_name))
-                    .set("font-size", section_font_size.to_string())
-                    .set("text-anchor", "middle")
-                    .set("dominant-baseline", "central"),
+                Path::new()
+                    .set("class", "pieLeaderLine")
+                    .set("d", leader_line),
             );
         }
 
-        current_angle = end_angle;
+        main_group = main_group.add(
+            Text::new(label.text)
+                .set("class", label.class)
+                .set("x", label.x)
+                .set("y", label.y)
+                .set("font-family", format!("{}, sans-serif", font_name))
+                .set("font-size", label.font_size.to_string())
+                .set("text-anchor", label.anchor)
+                .set("dominant-baseline", "central"),
+        );
     }
 
     // Add outer circle after segments to cover their outer stroke
@@ -185,82 +332,336 @@ pub fn render_pie_chart_svg(
             .set("cy", 0),
     );
 
+    if inner_radius > 0.0 {
+        main_group = main_group.add(
+            Circle::new()
+                .set("class", "pieOuterCircle")
+                .set("r", inner_radius)
+                .set("cx", 0)
+                .set("cy", 0),
+        );
+
+        if let Some(center_text) = pie_chart
+            .config
+            .as_ref()
+            .and_then(|c| c.theme_variables.get("pieCenterText"))
+        {
+            main_group = main_group.add(
+                Text::new(format_center_text(center_text, &slices, total))
+                    .set("class", "pieCenterText")
+                    .set("x", 0)
+                    .set("y", 0)
+                    .set("font-family", format!("{}, sans-serif", font_name))
+                    .set("text-anchor", "middle")
+                    .set("dominant-baseline", "central"),
+            );
+        }
+    }
+
     if let Some(title) = &pie_chart.title {
         main_group = main_group.add(
             Text::new(title.clone())
                 .set("class", "pieTitleText")
                 .set("x", 0)
-                .set("y", -final_radius - 30.0)
+                .set("y", -final_radius - legend_above_height - 30.0)
                 .set("font-family", format!("{}, sans-serif", font_name))
                 .set("text-anchor", "middle"),
         );
     }
 
-    // Add legend outside the main group, positioned with consistent right margin
-    for (i, data) in pie_chart.data.iter().enumerate() {
-        let legend_x = width as f64 - side_margin - legend_width; // Start of legend area with right margin
-        let legend_y = center_y - (pie_chart.data.len() as f64 * 11.0) + (i as f64 * 22.0);
-        let color = get_color_for_slice(pie_chart, i);
-
-        let legend_group = Group::new()
-            .set("class", "legend")
-            .set("transform", format!("translate({},{})", legend_x, legend_y));
-
-        let legend_group = legend_group
-            .add(
-                Rectangle::new()
-                    .set("width", 18)
-                    .set("height", 18)
-                    .set("fill", color)
-                    .set("stroke", pie_stroke_color)
-                    .set("fill-opacity", pie_opacity),
-            )
-            .add(
-                Text::new(format!("{} [{}]", data.label, data.value))
-                    .set("x", 22)
-                    .set("y", 14)
-                    .set("font-family", format!("{}, sans-serif", font_name)),
-            );
-
-        document = document.add(legend_group);
+    // Add legend outside the main group
+    let legend_origin = match legend_position {
+        LegendPosition::Right => Some((
+            width as f64 - side_margin - legend_layout.width,
+            center_y - legend_layout.height / 2.0,
+        )),
+        LegendPosition::Left => Some((side_margin, center_y - legend_layout.height / 2.0)),
+        LegendPosition::Top => Some((center_x - legend_layout.width / 2.0, content_top)),
+        LegendPosition::Bottom => Some((
+            center_x - legend_layout.width / 2.0,
+            center_y + final_radius + chart_to_legend_gap,
+        )),
+        LegendPosition::None => None,
+    };
+    if let Some((legend_x, legend_y)) = legend_origin {
+        document = document.add(render_legend(
+            &legend_labels,
+            &legend_colors,
+            legend_x,
+            legend_y,
+            &legend_layout,
+            &legend_config,
+        ));
     }
 
     (document.add(main_group), width, actual_height as u32)
 }
 
-fn calculate_legend_width(pie_chart: &PieChart, font_data: &Option<Vec<u8>>) -> f64 {
+/// A percentage label for a slice along with its measured size
+struct SliceLabel {
+    text: String,
+    width: f64,
+    height: f64,
+    font_size: f32,
+}
+
+/// A slice label positioned relative to the centre of the chart
+struct PlacedSliceLabel {
+    text: String,
+    x: f64,
+    y: f64,
+    class: &'static str,
+    anchor: &'static str,
+    font_size: f32,
+    leader_line: Option<String>,
+}
+
+fn measure_slice_labels(
+    pie_chart: &PieChart,
+    slices: &[PieChartData],
+    percentages: &[f64],
+    decimals: usize,
+    font_data: &Option<Vec<u8>>,
+) -> Vec<SliceLabel> {
     let font_size = parse_font_size(
-        get_theme_variable(pie_chart, "pieLegendTextSize", "17px"),
+        get_theme_variable(pie_chart, "pieSectionTextSize", "17px"),
         17.0,
     );
-    let icon_width = 18.0; // Width of the color rectangle
-    let icon_margin = 22.0; // Space between icon and text
-    let margin = 20.0; // Add more right margin for safety
-
-    // Find the longest legend text
-    let max_text_length = if let Some(font_data) = font_data {
-        pie_chart
-            .data
-            .iter()
-            .map(|data| {
-                measure_text_width(
-                    &format!("{} [{}]", data.label, data.value),
-                    font_data,
-                    font_size,
-                )
-            })
-            .fold(0.0f32, f32::max) as f64
-    } else {
-        // Fallback to character width estimation if font loading fails
-        let char_width = font_size as f64 * 0.53;
-        pie_chart
-            .data
-            .iter()
-            .map(|data| format!("{} [{}]", data.label, data.value).len() as f64 * char_width)
-            .fold(0.0, f64::max)
+    let template = get_theme_variable(pie_chart, "pieSliceLabelTemplate", "{percent}%");
+
+    let height = match font_data {
+        Some(font_data) => measure_text_height(font_data, font_size) as f64,
+        None => font_size as f64,
     };
 
-    icon_width + icon_margin + max_text_length + margin
+    slices
+        .iter()
+        .zip(percentages)
+        .map(|(data, percent)| {
+            let text = format_slice_template(template, data, *percent, decimals);
+            let width = match font_data {
+                Some(font_data) => measure_text_width(&text, font_data, font_size) as f64,
+                // Fallback to character width estimation if font loading fails
+                None => text.len() as f64 * font_size as f64 * 0.53,
+            };
+            SliceLabel {
+                text,
+                width,
+                height,
+                font_size,
+            }
+        })
+        .collect()
+}
+
+/// Fill in the `{label}`, `{value}` and `{percent}` placeholders of a legend or slice label
+/// template, percentages are shown with `pieLabelDecimals` decimals unless a format is given
+fn format_slice_template(
+    template: &str,
+    data: &PieChartData,
+    percent: f64,
+    decimals: usize,
+) -> String {
+    format_template(
+        template,
+        &[
+            ("label", TemplateValue::Text(&data.label)),
+            ("value", TemplateValue::Number(data.value, None)),
+            ("percent", TemplateValue::Number(percent, Some(decimals))),
+        ],
+    )
+}
+
+/// Fill in the `{total}` placeholder of the centre text. Without a format spec the total has as
+/// many decimals as the most precise slice, which hides the rounding errors of adding them up.
+fn format_center_text(template: &str, slices: &[PieChartData], total: f64) -> String {
+    let decimals = slices
+        .iter()
+        .map(|slice| {
+            slice
+                .value
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, fraction)| fraction.len())
+        })
+        .max()
+        .unwrap_or(0);
+    format_template(
+        template,
+        &[("total", TemplateValue::Number(total, Some(decimals)))],
+    )
+}
+
+fn get_label_decimals(pie_chart: &PieChart) -> usize {
+    let decimals_str = get_theme_variable(pie_chart, "pieLabelDecimals", "0");
+    decimals_str.parse::<usize>().unwrap_or_else(|_| {
+        eprintln!(
+            "Warning: Invalid pie label decimals '{}', using 0",
+            decimals_str
+        );
+        0
+    })
+}
+
+/// Round the percentage of each slice to `decimals` places using the largest remainder method,
+/// so that the rounded percentages always add up to 100
+fn largest_remainder_percentages(slices: &[PieChartData], total: f64, decimals: usize) -> Vec<f64> {
+    if total <= 0.0 {
+        return vec![0.0; slices.len()];
+    }
+
+    let scale = 10f64.powi(decimals as i32);
+    let exact: Vec<f64> = slices
+        .iter()
+        .map(|d| d.value / total * 100.0 * scale)
+        .collect();
+    let mut rounded: Vec<i64> = exact.iter().map(|v| v.floor() as i64).collect();
+
+    // Give the units lost by rounding down to the slices with the largest remainders
+    let remaining = (100.0 * scale).round() as i64 - rounded.iter().sum::<i64>();
+    let mut by_remainder: Vec<usize> = (0..exact.len()).collect();
+    by_remainder
+        .sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    for &idx in by_remainder.iter().take(remaining.max(0) as usize) {
+        rounded[idx] += 1;
+    }
+
+    rounded.iter().map(|&units| units as f64 / scale).collect()
+}
+
+/// Labels go in the middle of the ring for donuts
+fn slice_label_radius(radius: f64, inner_radius: f64) -> f64 {
+    if inner_radius > 0.0 {
+        (radius + inner_radius) / 2.0
+    } else {
+        radius * 0.75
+    }
+}
+
+/// Check whether a label fits inside its slice, using the length of the arc it sits on
+fn slice_label_fits(label: &SliceLabel, slice_angle: f64, radius: f64, inner_radius: f64) -> bool {
+    let padding = 4.0;
+    let arc_length = slice_angle * slice_label_radius(radius, inner_radius);
+    arc_length >= label.width.max(label.height) + padding
+        && radius - inner_radius >= label.height + padding
+}
+
+/// Position the slice labels. Labels that don't fit inside their slice are drawn to the side of
+/// the chart with a leader line, and moved apart vertically so they don't overlap each other
+fn layout_slice_labels(
+    labels: &[SliceLabel],
+    slice_angles: &[f64],
+    radius: f64,
+    inner_radius: f64,
+) -> Vec<PlacedSliceLabel> {
+    let label_radius = slice_label_radius(radius, inner_radius);
+    let mut placed = Vec::new();
+    // Outside labels on the (left, right) side as the label, mid angle and y position
+    let mut outside: [Vec<(&SliceLabel, f64, f64)>; 2] = [Vec::new(), Vec::new()];
+
+    let mut current_angle = -PI / 2.0;
+    for (label, &slice_angle) in labels.iter().zip(slice_angles) {
+        let mid_angle = current_angle + slice_angle / 2.0;
+        current_angle += slice_angle;
+
+        if slice_label_fits(label, slice_angle, radius, inner_radius) {
+            placed.push(PlacedSliceLabel {
+                text: label.text.clone(),
+                x: label_radius * mid_angle.cos(),
+                y: label_radius * mid_angle.sin(),
+                class: "slice",
+                anchor: "middle",
+                font_size: label.font_size,
+                leader_line: None,
+            });
+        } else {
+            let side = usize::from(mid_angle.cos() >= 0.0);
+            let y = (radius + LEADER_LINE_ELBOW) * mid_angle.sin();
+            outside[side].push((label, mid_angle, y));
+        }
+    }
+
+    let max_y = radius + LEADER_LINE_ELBOW;
+    for (side, mut side_labels) in outside.into_iter().enumerate() {
+        side_labels.sort_by(|a, b| a.2.total_cmp(&b.2));
+        let gap = |a: &SliceLabel, b: &SliceLabel| (a.height + b.height) / 2.0 + 2.0;
+
+        // Push overlapping labels down, then back up if they went past the bottom
+        for i in 1..side_labels.len() {
+            let min_y = side_labels[i - 1].2 + gap(side_labels[i - 1].0, side_labels[i].0);
+            side_labels[i].2 = side_labels[i].2.max(min_y);
+        }
+        if let Some(last) = side_labels.last_mut() {
+            last.2 = last.2.min(max_y);
+        }
+        for i in (0..side_labels.len().saturating_sub(1)).rev() {
+            let max_label_y = side_labels[i + 1].2 - gap(side_labels[i].0, side_labels[i + 1].0);
+            side_labels[i].2 = side_labels[i].2.min(max_label_y);
+        }
+
+        let direction = if side == 1 { 1.0 } else { -1.0 };
+        for (label, mid_angle, y) in side_labels {
+            let line_end_x = direction * (radius + LEADER_LINE_ELBOW + LEADER_LINE_LENGTH);
+            placed.push(PlacedSliceLabel {
+                text: label.text.clone(),
+                x: line_end_x + direction * LEADER_LINE_TEXT_GAP,
+                y,
+                class: "sliceOutside",
+                anchor: if side == 1 { "start" } else { "end" },
+                font_size: label.font_size,
+                leader_line: Some(format!(
+                    "M{},{} L{},{} L{},{}",
+                    radius * mid_angle.cos(),
+                    radius * mid_angle.sin(),
+                    (radius + LEADER_LINE_ELBOW) * mid_angle.cos(),
+                    y,
+                    line_end_x,
+                    y
+                )),
+            });
+        }
+    }
+
+    placed
+}
+
+/// Sort the slices from largest to smallest when `pieSort` is `descending` and merge slices
+/// below the `pieOtherThreshold` percentage into a single slice labelled with `pieOtherLabel`
+fn prepare_slices(pie_chart: &PieChart) -> Vec<PieChartData> {
+    let mut slices = pie_chart.data.clone();
+
+    match get_theme_variable(pie_chart, "pieSort", "none") {
+        "descending" => slices.sort_by(|a, b| b.value.total_cmp(&a.value)),
+        "none" => {}
+        sort => eprintln!(
+            "Warning: Invalid pie sort '{}', expected 'descending' or 'none'",
+            sort
+        ),
+    }
+
+    let threshold_str = get_theme_variable(pie_chart, "pieOtherThreshold", "0");
+    let threshold = threshold_str.parse::<f64>().unwrap_or_else(|_| {
+        eprintln!(
+            "Warning: Invalid pie other threshold '{}', slices will not be merged",
+            threshold_str
+        );
+        0.0
+    });
+    let total: f64 = slices.iter().map(|d| d.value).sum();
+    let is_small = |d: &PieChartData| total > 0.0 && d.value / total * 100.0 < threshold;
+
+    // A single small slice is kept as it is rather than being renamed
+    if slices.iter().filter(|d| is_small(d)).count() < 2 {
+        return slices;
+    }
+
+    let other_value = slices.iter().filter(|d| is_small(d)).map(|d| d.value).sum();
+    slices.retain(|d| !is_small(d));
+    slices.push(PieChartData {
+        label: get_theme_variable(pie_chart, "pieOtherLabel", "Other").to_string(),
+        value: other_value,
+    });
+    slices
 }
 
 fn get_color_for_slice(pie_chart: &PieChart, index: usize) -> &str {
@@ -300,6 +701,45 @@ fn parse_font_size(font_size_str: &str, default: f32) -> f32 {
     }
 }
 
+/// Create the path for a slice of a donut, an annular sector between the two radii
+fn create_donut_slice_path(
+    cx: f64,
+    cy: f64,
+    radius: f64,
+    inner_radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+) -> String {
+    let start_x = cx + radius * start_angle.cos();
+    let start_y = cy + radius * start_angle.sin();
+    let end_x = cx + radius * end_angle.cos();
+    let end_y = cy + radius * end_angle.sin();
+    let inner_start_x = cx + inner_radius * start_angle.cos();
+    let inner_start_y = cy + inner_radius * start_angle.sin();
+    let inner_end_x = cx + inner_radius * end_angle.cos();
+    let inner_end_y = cy + inner_radius * end_angle.sin();
+
+    let large_arc_flag = if end_angle - start_angle > PI { 1 } else { 0 };
+
+    format!(
+        "M{},{} A{},{},0,{},1,{},{} L{},{} A{},{},0,{},0,{},{} Z",
+        start_x,
+        start_y,
+        radius,
+        radius,
+        large_arc_flag,
+        end_x,
+        end_y,
+        inner_end_x,
+        inner_end_y,
+        inner_radius,
+        inner_radius,
+        large_arc_flag,
+        inner_start_x,
+        inner_start_y
+    )
+}
+
 fn create_pie_slice_path(
     cx: f64,
     cy: f64,
@@ -319,3 +759,174 @@ fn create_pie_slice_path(
         start_x, start_y, radius, radius, large_arc_flag, end_x, end_y, cx, cy
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::ChartConfig;
+
+    fn pie_chart(theme_variables: &[(&str, &str)], data: &[(&str, f64)]) -> PieChart {
+        PieChart {
+            config: Some(ChartConfig {
+                theme: "default".to_string(),
+                theme_variables: theme_variables
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                width: None,
+            }),
+            show_data: false,
+            title: None,
+            data: data
+                .iter()
+                .map(|(label, value)| PieChartData {
+                    label: label.to_string(),
+                    value: *value,
+                })
+                .collect(),
+        }
+    }
+
+    fn slice_values(slices: &[PieChartData]) -> Vec<(&str, f64)> {
+        slices
+            .iter()
+            .map(|slice| (slice.label.as_str(), slice.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_format_center_text() {
+        let chart = pie_chart(&[], &[("Done", 0.1), ("To Do", 0.2)]);
+        assert_eq!(
+            format_center_text("{total} points", &chart.data, 0.1 + 0.2),
+            "0.3 points"
+        );
+
+        let chart = pie_chart(&[], &[("Done", 1200.0), ("To Do", 34.0)]);
+        assert_eq!(format_center_text("{total}", &chart.data, 1234.0), "1234");
+        assert_eq!(
+            format_center_text("{total:,.1} points", &chart.data, 1234.0),
+            "1,234.0 points"
+        );
+    }
+
+    #[test]
+    fn test_prepare_slices_sorting() {
+        let data = [("Done", 10.0), ("Blocked", 40.0), ("Review", 20.0)];
+
+        let slices = prepare_slices(&pie_chart(&[("pieSort", "descending")], &data));
+        assert_eq!(
+            slice_values(&slices),
+            vec![("Blocked", 40.0), ("Review", 20.0), ("Done", 10.0)]
+        );
+
+        let slices = prepare_slices(&pie_chart(&[], &data));
+        assert_eq!(slice_values(&slices), data.to_vec());
+        // Invalid sorts warn and keep the order of the data
+        let slices = prepare_slices(&pie_chart(&[("pieSort", "sideways")], &data));
+        assert_eq!(slice_values(&slices), data.to_vec());
+    }
+
+    #[test]
+    fn test_prepare_slices_other() {
+        let data = [
+            ("Done", 90.0),
+            ("Blocked", 4.0),
+            ("Review", 1.0),
+            ("Test", 5.0),
+        ];
+
+        let slices = prepare_slices(&pie_chart(
+            &[("pieOtherThreshold", "5"), ("pieOtherLabel", "Rest")],
+            &data,
+        ));
+        assert_eq!(
+            slice_values(&slices),
+            vec![("Done", 90.0), ("Test", 5.0), ("Rest", 5.0)]
+        );
+
+        let slices = prepare_slices(&pie_chart(
+            &[("pieOtherThreshold", "6"), ("pieSort", "descending")],
+            &data,
+        ));
+        assert_eq!(slice_values(&slices), vec![("Done", 90.0), ("Other", 10.0)]);
+
+        // A single small slice keeps its own label
+        let slices = prepare_slices(&pie_chart(&[("pieOtherThreshold", "2")], &data));
+        assert_eq!(slice_values(&slices), data.to_vec());
+
+        // An invalid threshold warns and leaves the slices unmerged
+        let slices = prepare_slices(&pie_chart(&[("pieOtherThreshold", "lots")], &data));
+        assert_eq!(slice_values(&slices), data.to_vec());
+    }
+
+    fn slices(values: &[f64]) -> Vec<PieChartData> {
+        values
+            .iter()
+            .map(|value| PieChartData {
+                label: String::new(),
+                value: *value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_largest_remainder_percentages() {
+        // Ties go to the earliest slice
+        let percentages = largest_remainder_percentages(&slices(&[1.0, 1.0, 1.0]), 3.0, 0);
+        assert_eq!(percentages, vec![34.0, 33.0, 33.0]);
+
+        let percentages = largest_remainder_percentages(&slices(&[1.0, 1.0, 1.0]), 3.0, 1);
+        assert_eq!(percentages, vec![33.4, 33.3, 33.3]);
+        assert!((percentages.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+
+        let values = [13.0, 7.0, 2.0, 5.0, 11.0, 3.0, 17.0];
+        let total = values.iter().sum();
+        for decimals in 0..3 {
+            let percentages = largest_remainder_percentages(&slices(&values), total, decimals);
+            assert!((percentages.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        }
+
+        assert_eq!(
+            largest_remainder_percentages(&slices(&[0.0, 0.0]), 0.0, 0),
+            vec![0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_layout_slice_labels_spreads_outside_labels() {
+        let label = || SliceLabel {
+            text: "100%".to_string(),
+            width: 50.0,
+            height: 17.0,
+            font_size: 17.0,
+        };
+        // Five thin slices at the top right followed by one that takes up the rest of the chart
+        let top = [vec![0.05; 5], vec![2.0 * PI - 0.25]].concat();
+        // Five thin slices just before the bottom, whose labels have to be pushed back up
+        let bottom = [vec![PI - 0.25], vec![0.05; 5], vec![PI]].concat();
+
+        for slice_angles in [top, bottom] {
+            let labels: Vec<SliceLabel> = slice_angles.iter().map(|_| label()).collect();
+            let placed = layout_slice_labels(&labels, &slice_angles, 100.0, 0.0);
+            assert_eq!(placed.len(), slice_angles.len());
+
+            let mut outside_y: Vec<f64> = placed
+                .iter()
+                .filter(|label| label.class == "sliceOutside")
+                .inspect(|label| {
+                    assert_eq!(label.anchor, "start");
+                    assert!(label.leader_line.is_some());
+                })
+                .map(|label| label.y)
+                .collect();
+            assert_eq!(outside_y.len(), 5);
+            outside_y.sort_by(f64::total_cmp);
+            assert!(outside_y[4] <= 100.0 + LEADER_LINE_ELBOW);
+            let gap = 17.0 + 2.0;
+            for pair in outside_y.windows(2) {
+                assert!(pair[1] - pair[0] >= gap - 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use super::PieChart;
+use crate::common::writer::quote;
+
+impl PieChart {
+    /// The chart in mermaid syntax, preceded by the `%%{init}%%` line of any config
+    pub fn to_mermaid(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Write the chart in the syntax accepted by `parse_pie_chart`
+impl fmt::Display for PieChart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(config) = &self.config {
+            writeln!(f, "{}", config)?;
+        }
+
+        write!(f, "pie")?;
+        if self.show_data {
+            write!(f, " showData")?;
+        }
+        if let Some(title) = &self.title {
+            write!(f, " title {}", title)?;
+        }
+        writeln!(f)?;
+
+        for entry in &self.data {
+            writeln!(f, "  {}: {}", quote(&entry.label), entry.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::writer::strategies::{chart_config, quotable_text};
+    use crate::pie_chart::{parse_pie_chart, parse_pie_chart_content, PieChart, PieChartData};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_write_pie_chart() {
+        let input = r#"pie showData title Story points by status
+  "Done": 262
+  "To Do, later": 12.5
+  "Say #quot;blocked#quot;": -1
+"#;
+
+        let (_, pie_chart) = parse_pie_chart_content(input, None).unwrap();
+        assert_eq!(pie_chart.to_mermaid(), input);
+    }
+
+    fn pie_chart() -> impl Strategy<Value = PieChart> {
+        (
+            prop::option::of(chart_config()),
+            any::<bool>(),
+            // The title is the rest of the line after `title `
+            prop::option::of("[^\n\r]{0,16}"),
+            prop::collection::vec(
+                (quotable_text(), -1e6f64..1e6)
+                    .prop_map(|(label, value)| PieChartData { label, value }),
+                0..6,
+            ),
+        )
+            .prop_map(|(config, show_data, title, data)| PieChart {
+                config,
+                show_data,
+                title,
+                data,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_pie_chart_round_trip(pie_chart in pie_chart()) {
+            let written = pie_chart.to_mermaid();
+            let (remaining, parsed) = parse_pie_chart(&written).unwrap();
+            prop_assert_eq!(remaining, "");
+            prop_assert_eq!(parsed, pie_chart);
+        }
+    }
+}
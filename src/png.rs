@@ -1,7 +1,9 @@
 use resvg::tiny_skia;
+use resvg::usvg::fontdb::Database;
 use resvg::usvg::{Options, Tree};
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub enum PngError {
@@ -20,13 +22,10 @@ impl fmt::Display for PngError {
 
 impl Error for PngError {}
 
-pub fn svg_to_png(
-    svg_content: &str,
-    width: u32,
-    height: u32,
-    font_name: &str,
-) -> Result<Vec<u8>, PngError> {
-    let mut fontdb = resvg::usvg::fontdb::Database::new();
+/// Load the system fonts used to render text, this is slow so the database should be shared
+/// when rendering many charts
+pub fn load_font_database() -> Arc<Database> {
+    let mut fontdb = Database::new();
     fontdb.load_system_fonts();
 
     // Add fallback fonts for Linux systems
@@ -34,8 +33,28 @@ pub fn svg_to_png(
     fontdb.set_sans_serif_family("Liberation Sans");
     fontdb.set_monospace_family("Liberation Mono");
 
+    Arc::new(fontdb)
+}
+
+pub fn svg_to_png(
+    svg_content: &str,
+    width: u32,
+    height: u32,
+    font_name: &str,
+) -> Result<Vec<u8>, PngError> {
+    svg_to_png_with_fonts(svg_content, width, height, font_name, load_font_database())
+}
+
+/// Render an SVG to a PNG using fonts that have already been loaded
+pub fn svg_to_png_with_fonts(
+    svg_content: &str,
+    width: u32,
+    height: u32,
+    font_name: &str,
+    fontdb: Arc<Database>,
+) -> Result<Vec<u8>, PngError> {
     let options = Options {
-        fontdb: std::sync::Arc::new(fontdb),
+        fontdb,
         font_family: font_name.to_string(),
         font_size: 12.0,
         ..Options::default()
@@ -0,0 +1,186 @@
+use super::{GroupBy, SortBy, WorkItem, WorkItemMovement};
+use std::cmp::Ordering;
+
+/// Items that are drawn together in a swimlane
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkItemGroup<'a> {
+    /// The swimlane label, items that are not in a group have no label
+    pub label: Option<String>,
+    pub items: Vec<&'a WorkItem>,
+}
+
+/// Split an id into the text before any number at the end and the number,
+/// e.g. `PJ-12` is split into `PJ-` and `12`
+fn split_id_number(id: &str) -> (&str, Option<u64>) {
+    let prefix = id.trim_end_matches(|c: char| c.is_ascii_digit());
+    (prefix, id[prefix.len()..].parse().ok())
+}
+
+/// The id without the number at the end along with any separator before it, e.g. `PJ` for
+/// `PJ-12`. Ids without a number are their own prefix.
+pub fn id_prefix(id: &str) -> &str {
+    let prefix = split_id_number(id).0.trim_end_matches(['-', '_']);
+    if prefix.is_empty() {
+        id
+    } else {
+        prefix
+    }
+}
+
+fn compare_ids(a: &str, b: &str) -> Ordering {
+    let (a_prefix, a_number) = split_id_number(a);
+    let (b_prefix, b_number) = split_id_number(b);
+    a_prefix
+        .cmp(b_prefix)
+        .then(a_number.cmp(&b_number))
+        .then_with(|| a.cmp(b))
+}
+
+/// Group the items into swimlanes and sort the items within each group. Groups are ordered by
+/// the first item in each one, other than groups of columns which follow the column order.
+pub fn group_work_items(chart: &WorkItemMovement) -> Vec<WorkItemGroup<'_>> {
+    let group_label = |item: &WorkItem| -> Option<String> {
+        match chart.group_by {
+            Some(GroupBy::Prefix) => Some(id_prefix(&item.id).to_string()),
+            Some(GroupBy::Column) => Some(
+                chart
+                    .column_index(item.to_state())
+                    .map_or(item.to_state(), |index| &chart.columns[index])
+                    .to_string(),
+            ),
+            None => item.section.clone(),
+        }
+    };
+
+    let mut groups: Vec<WorkItemGroup> = Vec::new();
+    for item in &chart.items {
+        let label = group_label(item);
+        match groups.iter_mut().find(|group| group.label == label) {
+            Some(group) => group.items.push(item),
+            None => groups.push(WorkItemGroup {
+                label,
+                items: vec![item],
+            }),
+        }
+    }
+
+    if chart.group_by == Some(GroupBy::Column) {
+        groups.sort_by_key(|group| {
+            group
+                .items
+                .first()
+                .and_then(|item| chart.column_index(item.to_state()))
+        });
+    }
+
+    if let Some(sort_by) = chart.sort_by {
+        for group in &mut groups {
+            match sort_by {
+                SortBy::Column => group
+                    .items
+                    .sort_by_key(|item| chart.column_index(item.to_state())),
+                SortBy::Points => group
+                    .items
+                    .sort_by(|a, b| b.to_points().total_cmp(&a.to_points())),
+                SortBy::Id => group.items.sort_by(|a, b| compare_ids(&a.id, &b.id)),
+            }
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::work_item_movement::parse_work_item_movement;
+
+    fn group_ids<'a>(groups: &'a [WorkItemGroup]) -> Vec<(Option<&'a str>, Vec<&'a str>)> {
+        groups
+            .iter()
+            .map(|group| {
+                (
+                    group.label.as_deref(),
+                    group.items.iter().map(|item| item.id.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_section_and_sort_by_id() {
+        let input = r#"work-item-movement
+  columns [To Do, Done]
+  sort by id
+  section 'Team A'
+  PJ-10 To Do: 1 -> Done: 1
+  PJ-2 To Do: 1 -> Done: 1
+  section 'Team B'
+  AB-1 To Do: 1 -> Done: 1
+  section 'Team A'
+  PJ-1 To Do: 1 -> Done: 1
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        let groups = group_work_items(&chart);
+        assert_eq!(
+            group_ids(&groups),
+            vec![
+                (Some("Team A"), vec!["PJ-1", "PJ-2", "PJ-10"]),
+                (Some("Team B"), vec!["AB-1"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_prefix_and_sort_by_points() {
+        let input = r#"work-item-movement
+  columns [To Do, Done]
+  group by prefix
+  sort by points
+  PJ-1 To Do: 1 -> Done: 2
+  AB-1 To Do: 1 -> Done: 1
+  PJ-2 To Do: 1 -> Done: 5
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        let groups = group_work_items(&chart);
+        assert_eq!(
+            group_ids(&groups),
+            vec![
+                (Some("PJ"), vec!["PJ-2", "PJ-1"]),
+                (Some("AB"), vec!["AB-1"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_column() {
+        let input = r#"work-item-movement
+  columns [To Do, In Progress, Done]
+  group by column
+  PJ-1 To Do: 1 -> Done: 1
+  PJ-2 Done: 1 -> in progress: 1
+  PJ-3 To Do: 1 -> In Progress: 1
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        let groups = group_work_items(&chart);
+        assert_eq!(
+            group_ids(&groups),
+            vec![
+                (Some("In Progress"), vec!["PJ-2", "PJ-3"]),
+                (Some("Done"), vec!["PJ-1"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id_prefix() {
+        assert_eq!(id_prefix("PJ-12"), "PJ");
+        assert_eq!(id_prefix("AB2_7"), "AB2");
+        assert_eq!(id_prefix("#123"), "#");
+        assert_eq!(id_prefix("123"), "123");
+        assert_eq!(id_prefix("spike"), "spike");
+    }
+}
@@ -0,0 +1,580 @@
+use super::{WorkItem, WorkItemMovement, WorkItemState};
+use serde_json::Value;
+use std::fmt;
+
+/// The column used for issues that were created during the window
+pub const NOT_EXISTING_COLUMN: &str = "Not Existing";
+
+/// The story points field of Jira cloud projects
+pub const DEFAULT_POINTS_FIELD: &str = "customfield_10016";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JiraImportOptions {
+    /// The first day of the window as `YYYY-MM-DD`
+    pub from: String,
+    /// The last day of the window as `YYYY-MM-DD`, changes made during this day are included
+    pub to: String,
+    /// The id of the story points field such as `customfield_10016`, or its name when the
+    /// export includes the field names
+    pub points_field: String,
+    /// The order of the columns, statuses missing from this list are added after it
+    pub columns: Option<Vec<String>>,
+    /// Include issues with the same status and points at the start and end of the window
+    pub include_unchanged: bool,
+}
+
+impl Default for JiraImportOptions {
+    fn default() -> Self {
+        Self {
+            from: String::new(),
+            to: String::new(),
+            points_field: DEFAULT_POINTS_FIELD.to_string(),
+            columns: None,
+            include_unchanged: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct JiraImportError {
+    pub message: String,
+}
+
+impl fmt::Display for JiraImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for JiraImportError {}
+
+fn import_error(message: impl Into<String>) -> JiraImportError {
+    JiraImportError {
+        message: message.into(),
+    }
+}
+
+/// Days since 1970-01-01 for a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Parse a `YYYY-MM-DD` date into seconds since the epoch at the start of the day in UTC
+fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY)
+}
+
+/// Parse a Jira timestamp such as `2024-05-01T10:30:00.000+1000` into seconds since the epoch
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let (date, time) = timestamp.split_once('T')?;
+    let date = parse_date(date)?;
+
+    // The offset follows the time as `Z`, `+hhmm` or `+hh:mm`
+    let offset_start = time.find(['Z', '+', '-']).unwrap_or(time.len());
+    let (time, offset) = time.split_at(offset_start);
+    let offset = match offset.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let digits: String = offset[1..].chars().filter(|c| *c != ':').collect();
+            if digits.len() != 4 {
+                return None;
+            }
+            let hours: i64 = digits[..2].parse().ok()?;
+            let minutes: i64 = digits[2..].parse().ok()?;
+            let offset = hours * 3600 + minutes * 60;
+            if sign == '+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => 0,
+    };
+
+    let mut fields = time.split(':');
+    let hours: i64 = fields.next()?.parse().ok()?;
+    let minutes: i64 = fields.next()?.parse().ok()?;
+    let seconds: f64 = fields.next().unwrap_or("0").parse().ok()?;
+
+    Some(date + hours * 3600 + minutes * 60 + seconds as i64 - offset)
+}
+
+fn parse_points(value: &Value) -> f64 {
+    match value {
+        Value::Number(number) => number.as_f64().unwrap_or(0.0),
+        Value::String(text) => text.trim().parse().unwrap_or(0.0),
+        _ => 0.0,
+    }
+}
+
+/// A single change from an issue's changelog
+struct Change {
+    created: i64,
+    is_status: bool,
+    from: Value,
+}
+
+/// The state of an issue at the start and end of the window
+struct IssueWindow {
+    key: String,
+    summary: Option<String>,
+    /// The issue's current status and its category, used to order the columns
+    status: String,
+    status_category: Option<String>,
+    start: Option<WorkItemState>,
+    end: WorkItemState,
+}
+
+/// Work out the status and points of an issue at the start and end of the window by undoing the
+/// changes made after each point in time. Returns `None` for issues created after the window.
+fn issue_window(
+    issue: &Value,
+    points_field_id: &str,
+    points_field: &str,
+    start: i64,
+    end: i64,
+) -> Result<Option<IssueWindow>, JiraImportError> {
+    let key = issue["key"]
+        .as_str()
+        .ok_or_else(|| import_error("Found an issue without a key"))?;
+    let fields = &issue["fields"];
+    let status = fields["status"]["name"]
+        .as_str()
+        .ok_or_else(|| import_error(format!("Issue {} has no status", key)))?;
+    let created = fields["created"].as_str().and_then(parse_timestamp);
+    if created.is_some_and(|created| created >= end) {
+        return Ok(None);
+    }
+
+    let mut changes = Vec::new();
+    for history in issue["changelog"]["histories"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let Some(history_created) = history["created"].as_str().and_then(parse_timestamp) else {
+            return Err(import_error(format!(
+                "Issue {} has a changelog entry with an invalid date",
+                key
+            )));
+        };
+        for item in history["items"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            let field = item["field"].as_str().unwrap_or_default();
+            let field_id = item["fieldId"].as_str().unwrap_or_default();
+            let is_status = field == "status";
+            let is_points =
+                field_id == points_field_id || field == points_field || field_id == points_field;
+            if is_status || is_points {
+                changes.push(Change {
+                    created: history_created,
+                    is_status,
+                    from: item["fromString"].clone(),
+                });
+            }
+        }
+    }
+    // Undo the newest changes first
+    changes.sort_by_key(|change| std::cmp::Reverse(change.created));
+
+    let state_at = |time: i64| {
+        let mut state = WorkItemState {
+            name: status.to_string(),
+            points: parse_points(&fields[points_field_id]),
+        };
+        for change in changes.iter().take_while(|change| change.created >= time) {
+            if change.is_status {
+                if let Some(name) = change.from.as_str() {
+                    state.name = name.to_string();
+                }
+            } else {
+                state.points = parse_points(&change.from);
+            }
+        }
+        state
+    };
+
+    Ok(Some(IssueWindow {
+        key: key.to_string(),
+        summary: fields["summary"].as_str().map(str::to_string),
+        status: status.to_string(),
+        status_category: fields["status"]["statusCategory"]["key"]
+            .as_str()
+            .map(str::to_string),
+        start: if created.is_some_and(|created| created >= start) {
+            None
+        } else {
+            Some(state_at(start))
+        },
+        end: state_at(end),
+    }))
+}
+
+/// Order the statuses by their category so that to do statuses are before statuses in progress
+/// which are before done statuses. The export only has the category of each issue's current
+/// status, so a status gets the category of the first issue that is still in it at the end of
+/// the window and is ranked with the statuses in progress otherwise. Statuses with the same
+/// rank are ordered by when they are first seen.
+fn default_columns(windows: &[IssueWindow]) -> Vec<String> {
+    let category_order = |category: Option<&str>| match category {
+        Some("new") => 0,
+        Some("done") => 2,
+        _ => 1,
+    };
+
+    let mut statuses: Vec<(String, Option<usize>)> = Vec::new();
+    for window in windows {
+        let end_category = (window.end.name == window.status)
+            .then(|| category_order(window.status_category.as_deref()));
+        let states = window
+            .start
+            .iter()
+            .map(|state| (state, None))
+            .chain([(&window.end, end_category)]);
+        for (state, category) in states {
+            match statuses
+                .iter_mut()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&state.name))
+            {
+                Some((_, existing)) => {
+                    if existing.is_none() {
+                        *existing = category;
+                    }
+                }
+                None => statuses.push((state.name.clone(), category)),
+            }
+        }
+    }
+    statuses.sort_by_key(|(_, category)| category.unwrap_or(1));
+    statuses.into_iter().map(|(name, _)| name).collect()
+}
+
+/// Build a work item movement chart from Jira issues exported with their changelog, such as the
+/// response of the search API with `expand=changelog`, or an array of issues. Each item moves
+/// from its status and points at the start of the window to its status and points at the end.
+/// Times are compared in UTC and issues created during the window start in the
+/// `Not Existing` column with no points.
+pub fn import_jira_changelog(
+    json: &str,
+    options: &JiraImportOptions,
+) -> Result<WorkItemMovement, JiraImportError> {
+    let start = parse_date(&options.from)
+        .ok_or_else(|| import_error(format!("Invalid from date '{}'", options.from)))?;
+    let end = parse_date(&options.to)
+        .ok_or_else(|| import_error(format!("Invalid to date '{}'", options.to)))?
+        + SECONDS_PER_DAY;
+    if end <= start {
+        return Err(import_error("The to date must not be before the from date"));
+    }
+
+    let root: Value =
+        serde_json::from_str(json).map_err(|e| import_error(format!("Invalid JSON: {}", e)))?;
+    let issues = match &root {
+        Value::Array(issues) => issues,
+        _ => root["issues"]
+            .as_array()
+            .ok_or_else(|| import_error("Expected an array of issues or an object with issues"))?,
+    };
+
+    // Exports with `expand=names` map field ids to names so the points field can be given by name
+    let points_field_id = root["names"]
+        .as_object()
+        .and_then(|names| {
+            names
+                .iter()
+                .find(|(_, name)| name.as_str() == Some(options.points_field.as_str()))
+                .map(|(id, _)| id.as_str())
+        })
+        .unwrap_or(&options.points_field);
+
+    let mut windows = Vec::new();
+    for issue in issues {
+        if let Some(window) =
+            issue_window(issue, points_field_id, &options.points_field, start, end)?
+        {
+            windows.push(window);
+        }
+    }
+
+    let mut columns = match options.columns {
+        Some(ref columns) => {
+            let mut columns = columns.clone();
+            for status in default_columns(&windows) {
+                if !columns
+                    .iter()
+                    .any(|column| column.eq_ignore_ascii_case(&status))
+                {
+                    eprintln!(
+                        "Warning: Status '{}' is not in the columns, adding it to the end",
+                        status
+                    );
+                    columns.push(status);
+                }
+            }
+            columns
+        }
+        None => default_columns(&windows),
+    };
+    if windows.iter().any(|window| window.start.is_none())
+        && !columns
+            .iter()
+            .any(|column| column.eq_ignore_ascii_case(NOT_EXISTING_COLUMN))
+    {
+        columns.insert(0, NOT_EXISTING_COLUMN.to_string());
+    }
+
+    let items = windows
+        .into_iter()
+        .filter_map(|window| {
+            let start = window.start.unwrap_or(WorkItemState {
+                name: NOT_EXISTING_COLUMN.to_string(),
+                points: 0.0,
+            });
+            if !options.include_unchanged && start == window.end {
+                return None;
+            }
+            Some(WorkItem {
+                id: window.key,
+                title: window.summary,
+                section: None,
+                states: vec![start, window.end],
+            })
+        })
+        .collect();
+
+    Ok(WorkItemMovement {
+        config: None,
+        title: Some(format!(
+            "Work item changes from {} to {}",
+            options.from, options.to
+        )),
+        show_summary: false,
+        columns,
+        items,
+        sort_by: None,
+        group_by: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISSUES: &str = r#"{
+  "names": { "customfield_10016": "Story Points" },
+  "issues": [
+    {
+      "key": "PJ-1",
+      "fields": {
+        "summary": "Login page",
+        "created": "2024-04-01T09:00:00.000+0000",
+        "status": { "name": "Done", "statusCategory": { "key": "done" } },
+        "customfield_10016": 5
+      },
+      "changelog": {
+        "histories": [
+          {
+            "created": "2024-05-20T10:00:00.000+0000",
+            "items": [{ "field": "status", "fromString": "In Progress", "toString": "Done" }]
+          },
+          {
+            "created": "2024-05-03T10:00:00.000+0000",
+            "items": [
+              { "field": "status", "fromString": "To Do", "toString": "In Progress" },
+              { "field": "Story Points", "fieldId": "customfield_10016", "fromString": "3", "toString": "5" }
+            ]
+          },
+          {
+            "created": "2024-04-20T10:00:00.000+0000",
+            "items": [{ "field": "Story Points", "fieldId": "customfield_10016", "fromString": null, "toString": "3" }]
+          }
+        ]
+      }
+    },
+    {
+      "key": "PJ-2",
+      "fields": {
+        "summary": "Logout",
+        "created": "2024-05-10T09:00:00.000+1000",
+        "status": { "name": "In Progress", "statusCategory": { "key": "indeterminate" } },
+        "customfield_10016": 2
+      },
+      "changelog": {
+        "histories": [
+          {
+            "created": "2024-05-11T10:00:00.000+1000",
+            "items": [{ "field": "status", "fromString": "To Do", "toString": "In Progress" }]
+          }
+        ]
+      }
+    },
+    {
+      "key": "PJ-3",
+      "fields": {
+        "created": "2024-04-01T09:00:00.000+0000",
+        "status": { "name": "To Do", "statusCategory": { "key": "new" } },
+        "customfield_10016": 1
+      },
+      "changelog": { "histories": [] }
+    },
+    {
+      "key": "PJ-4",
+      "fields": {
+        "created": "2024-06-01T09:00:00.000+0000",
+        "status": { "name": "To Do", "statusCategory": { "key": "new" } }
+      }
+    }
+  ]
+}"#;
+
+    fn options(from: &str, to: &str) -> JiraImportOptions {
+        JiraImportOptions {
+            from: from.to_string(),
+            to: to.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn state(name: &str, points: f64) -> WorkItemState {
+        WorkItemState {
+            name: name.to_string(),
+            points,
+        }
+    }
+
+    #[test]
+    fn test_import_jira_changelog() {
+        let chart = import_jira_changelog(ISSUES, &options("2024-05-01", "2024-05-15")).unwrap();
+
+        assert_eq!(chart.columns, vec!["Not Existing", "To Do", "In Progress"]);
+        assert_eq!(chart.items.len(), 2);
+
+        assert_eq!(chart.items[0].id, "PJ-1");
+        assert_eq!(chart.items[0].title.as_deref(), Some("Login page"));
+        assert_eq!(
+            chart.items[0].states,
+            vec![state("To Do", 3.0), state("In Progress", 5.0)]
+        );
+
+        assert_eq!(chart.items[1].id, "PJ-2");
+        assert_eq!(
+            chart.items[1].states,
+            vec![state("Not Existing", 0.0), state("In Progress", 2.0)]
+        );
+    }
+
+    #[test]
+    fn test_import_jira_changelog_options() {
+        let chart = import_jira_changelog(
+            ISSUES,
+            &JiraImportOptions {
+                points_field: "Story Points".to_string(),
+                columns: Some(vec!["To Do".to_string(), "Done".to_string()]),
+                include_unchanged: true,
+                ..options("2024-04-02", "2024-05-31")
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            chart.columns,
+            vec!["Not Existing", "To Do", "Done", "In Progress"]
+        );
+        let items: Vec<(&str, &[WorkItemState])> = chart
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item.states.as_slice()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("PJ-1", &[state("To Do", 0.0), state("Done", 5.0)][..]),
+                (
+                    "PJ-2",
+                    &[state("Not Existing", 0.0), state("In Progress", 2.0)][..]
+                ),
+                ("PJ-3", &[state("To Do", 1.0), state("To Do", 1.0)][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_jira_changelog_past_window_columns() {
+        // PJ-1 was in progress at the end of the window and is done now, so its current status
+        // category doesn't say where In Progress goes
+        let issues = r#"[
+          {
+            "key": "PJ-1",
+            "fields": {
+              "created": "2024-04-01T09:00:00.000+0000",
+              "status": { "name": "Done", "statusCategory": { "key": "done" } }
+            },
+            "changelog": {
+              "histories": [
+                {
+                  "created": "2024-06-01T10:00:00.000+0000",
+                  "items": [{ "field": "status", "fromString": "In Progress", "toString": "Done" }]
+                }
+              ]
+            }
+          },
+          {
+            "key": "PJ-2",
+            "fields": {
+              "created": "2024-04-01T09:00:00.000+0000",
+              "status": { "name": "In Review", "statusCategory": { "key": "indeterminate" } }
+            }
+          },
+          {
+            "key": "PJ-3",
+            "fields": {
+              "created": "2024-04-01T09:00:00.000+0000",
+              "status": { "name": "Backlog", "statusCategory": { "key": "new" } }
+            }
+          }
+        ]"#;
+
+        let chart = import_jira_changelog(
+            issues,
+            &JiraImportOptions {
+                include_unchanged: true,
+                ..options("2024-05-01", "2024-05-15")
+            },
+        )
+        .unwrap();
+        assert_eq!(chart.columns, vec!["Backlog", "In Progress", "In Review"]);
+    }
+
+    #[test]
+    fn test_import_jira_changelog_errors() {
+        assert!(import_jira_changelog(ISSUES, &options("2024-05-01", "2024-04-01")).is_err());
+        assert!(import_jira_changelog(ISSUES, &options("May 1st", "2024-05-15")).is_err());
+        assert!(import_jira_changelog("{}", &options("2024-05-01", "2024-05-15")).is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-02T00:00:00.000+0000"), Some(86400));
+        assert_eq!(parse_timestamp("1970-01-02T10:00:00.000+1000"), Some(86400));
+        assert_eq!(parse_timestamp("1970-01-01T23:00:00-01:00"), Some(86400));
+        assert_eq!(parse_timestamp("2024-03-01T00:00:00Z"), Some(1709251200));
+        assert_eq!(parse_timestamp("2024-03-01"), None);
+    }
+}
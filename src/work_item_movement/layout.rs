@@ -0,0 +1,740 @@
+use super::{group_work_items, WorkItem, WorkItemGroup, WorkItemMovement};
+use crate::common::renderer::LegendLayout;
+use crate::font::{measure_text_height, measure_text_width};
+
+pub const MARGIN: f64 = 20.0;
+pub const TITLE_FONT_SIZE: f64 = 20.0;
+pub const COLUMN_FONT_SIZE: f64 = 16.0;
+pub const ITEM_FONT_SIZE: f64 = 14.0;
+pub const CIRCLE_FONT_SIZE: f64 = 16.0;
+pub const SUMMARY_FONT_SIZE: f64 = 12.0;
+pub const CIRCLE_RADIUS: f64 = 15.0;
+pub const ARROW_SIZE: f64 = 12.0;
+
+const TITLE_GAP: f64 = 20.0;
+const COLUMN_HEIGHT: f64 = 40.0;
+/// Gap between the column labels and the first item
+const COLUMN_TO_ITEMS_GAP: f64 = 20.0;
+/// Extra pixels above/below circles for the column lines
+const LINE_EXTENSION: f64 = 15.0;
+/// Distance from the line to the start of the text for vertical arrows
+const VERTICAL_LABEL_OFFSET: f64 = 5.0;
+/// Distance from the line to the bottom of the text for horizontal arrows
+const HORIZONTAL_LABEL_OFFSET: f64 = 5.0;
+const SWIMLANE_LABEL_HEIGHT: f64 = ITEM_FONT_SIZE + 10.0;
+const SUMMARY_LINE_HEIGHT: f64 = 16.0;
+const SUMMARY_GAP: f64 = 15.0;
+const LEGEND_GAP: f64 = 20.0;
+
+/// The vertical spacing of the items, which shrinks when the chart is taller than its maximum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemSpacing {
+    /// Space between one item and the next, or between the rows of an item
+    pub item_height: f64,
+    /// Space between circles in vertical arrows
+    pub vertical_arrow_spacing: f64,
+}
+
+pub const DEFAULT_SPACING: ItemSpacing = ItemSpacing {
+    item_height: 50.0,
+    vertical_arrow_spacing: 80.0,
+};
+
+/// The most compact spacing that still leaves room for the item labels and arrow heads
+pub const MIN_SPACING: ItemSpacing = ItemSpacing {
+    item_height: 36.0,
+    vertical_arrow_spacing: 50.0,
+};
+
+/// The number of steps taken between the default and minimum spacing when compacting
+const COMPACTION_STEPS: usize = 10;
+
+impl ItemSpacing {
+    /// The spacing a fraction of the way from the default spacing to the minimum spacing
+    fn compacted(fraction: f64) -> Self {
+        let interpolate = |default: f64, min: f64| default - (default - min) * fraction;
+        ItemSpacing {
+            item_height: interpolate(DEFAULT_SPACING.item_height, MIN_SPACING.item_height),
+            vertical_arrow_spacing: interpolate(
+                DEFAULT_SPACING.vertical_arrow_spacing,
+                MIN_SPACING.vertical_arrow_spacing,
+            ),
+        }
+    }
+}
+
+/// A circle drawn for one of the states an item moved through
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircleLayout {
+    pub column: usize,
+    pub x: f64,
+    pub y: f64,
+    pub points: f64,
+    /// The change in points from the previous state, which is zero for the first state
+    pub points_change: f64,
+}
+
+/// How consecutive circles of an item are connected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentKind {
+    /// Down to a circle in the same column
+    Vertical,
+    /// Across to a circle on the same row
+    Horizontal,
+    /// Down to a new row and then across, used when a horizontal arrow would overlap an
+    /// earlier arrow on the same row such as when an item moves backwards
+    Elbow,
+}
+
+/// An arrow between two circles
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowLayout {
+    pub kind: SegmentKind,
+    /// The columns of the circles the arrow goes from and to
+    pub from_column: usize,
+    pub to_column: usize,
+    /// The line from the edge of the previous circle to the base of the arrow head
+    pub line: Vec<(f64, f64)>,
+    /// The tip of the arrow head followed by the two corners of its base
+    pub head: [(f64, f64); 3],
+}
+
+/// How a label is aligned to its position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelAnchor {
+    /// Centred horizontally with the bottom of the text at the position
+    Above,
+    /// Starting at the position, centred vertically
+    Start,
+    /// Ending at the position, centred vertically
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelLayout {
+    pub x: f64,
+    pub y: f64,
+    pub anchor: LabelAnchor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemLayout<'a> {
+    pub item: &'a WorkItem,
+    pub circles: Vec<CircleLayout>,
+    pub arrows: Vec<ArrowLayout>,
+    /// The label is drawn against the first arrow, items without arrows have no label
+    pub label: Option<LabelLayout>,
+}
+
+impl ItemLayout<'_> {
+    /// The y position of the lowest circle
+    pub fn bottom(&self) -> f64 {
+        self.circles
+            .iter()
+            .map(|circle| circle.y)
+            .fold(f64::MIN, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwimlaneLayout {
+    pub label: String,
+    /// The position of the group in the chart, used to alternate the swimlane colours
+    pub group_index: usize,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub label_x: f64,
+    pub label_y: f64,
+}
+
+/// A set of columns along with the items drawn in them, charts that are too tall are split into
+/// two panels side by side
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelLayout<'a> {
+    pub column_positions: Vec<f64>,
+    pub column_line_top: f64,
+    pub column_line_bottom: f64,
+    pub items: Vec<ItemLayout<'a>>,
+    pub swimlanes: Vec<SwimlaneLayout>,
+}
+
+/// The positions of the summary footer lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryLayout {
+    /// The y position of the line separating the summary from the items
+    pub top: f64,
+    pub column_positions: Vec<f64>,
+    /// The y positions of the entered, left and net lines under each column
+    pub column_line_ys: [f64; 3],
+    pub scope_y: f64,
+}
+
+/// The positions of everything drawn in a work item movement chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkItemMovementLayout<'a> {
+    pub width: f64,
+    pub height: f64,
+    pub title_y: Option<f64>,
+    pub column_label_y: f64,
+    pub spacing: ItemSpacing,
+    pub panels: Vec<PanelLayout<'a>>,
+    pub summary: Option<SummaryLayout>,
+    /// The top of the legend, which is centred horizontally
+    pub legend_y: f64,
+}
+
+/// Measure column text widths to calculate proper positioning
+fn measure_column_widths(columns: &[String], font_data: &Option<Vec<u8>>) -> Vec<f64> {
+    columns
+        .iter()
+        .map(|col| {
+            if let Some(ref font_data) = font_data {
+                measure_text_width(col, font_data, COLUMN_FONT_SIZE as f32) as f64
+            } else {
+                col.len() as f64 * 8.0
+            }
+        })
+        .collect()
+}
+
+/// Spread the columns between `left` and `right` so the first and last column labels line up
+/// with the edges
+fn calculate_column_positions(column_widths: &[f64], left: f64, right: f64) -> Vec<f64> {
+    let num_columns = column_widths.len();
+    if num_columns <= 1 {
+        return vec![(left + right) / 2.0; num_columns];
+    }
+
+    // Calculate the exact positions for first and last lines
+    let first_line_pos = left + column_widths[0] / 2.0;
+    let last_line_pos = right - column_widths[num_columns - 1] / 2.0;
+
+    // First and last are fixed with the middle positions distributed evenly between them
+    let spacing = (last_line_pos - first_line_pos) / (num_columns - 1) as f64;
+    (0..num_columns)
+        .map(|i| first_line_pos + i as f64 * spacing)
+        .collect()
+}
+
+/// Build the arrow between two circles, the line stops at the base of the arrow head which
+/// touches the edge of the next circle
+fn layout_arrow(kind: SegmentKind, from: &CircleLayout, to: &CircleLayout) -> ArrowLayout {
+    match kind {
+        SegmentKind::Vertical => {
+            let tip_y = to.y - CIRCLE_RADIUS;
+            let base_y = tip_y - ARROW_SIZE;
+            ArrowLayout {
+                kind,
+                from_column: from.column,
+                to_column: to.column,
+                line: vec![(from.x, from.y + CIRCLE_RADIUS), (to.x, base_y)],
+                head: [
+                    (to.x, tip_y),
+                    (to.x - ARROW_SIZE / 2.0, base_y),
+                    (to.x + ARROW_SIZE / 2.0, base_y),
+                ],
+            }
+        }
+        SegmentKind::Horizontal | SegmentKind::Elbow => {
+            let direction = if to.x > from.x { 1.0 } else { -1.0 };
+            let tip_x = to.x - direction * CIRCLE_RADIUS;
+            let base_x = tip_x - direction * ARROW_SIZE;
+            let line = if kind == SegmentKind::Horizontal {
+                vec![(from.x + direction * CIRCLE_RADIUS, from.y), (base_x, to.y)]
+            } else {
+                // Drop down from the previous circle to the new row then head across
+                vec![
+                    (from.x, from.y + CIRCLE_RADIUS),
+                    (from.x, to.y),
+                    (base_x, to.y),
+                ]
+            };
+            ArrowLayout {
+                kind,
+                from_column: from.column,
+                to_column: to.column,
+                line,
+                head: [
+                    (tip_x, to.y),
+                    (base_x, to.y - ARROW_SIZE / 2.0),
+                    (base_x, to.y + ARROW_SIZE / 2.0),
+                ],
+            }
+        }
+    }
+}
+
+/// Position the circles, arrows and label of an item with its first state at `top`
+fn layout_item<'a>(
+    item: &'a WorkItem,
+    chart: &WorkItemMovement,
+    column_positions: &[f64],
+    top: f64,
+    spacing: ItemSpacing,
+) -> ItemLayout<'a> {
+    let mut circles: Vec<CircleLayout> = Vec::with_capacity(item.states.len());
+    let mut arrows = Vec::new();
+    // The horizontal extents of the arrows drawn on the current row
+    let mut row_spans: Vec<(f64, f64)> = Vec::new();
+
+    for state in &item.states {
+        let column = chart.column_index(&state.name).unwrap_or(0);
+        let x = column_positions[column];
+
+        let (kind, y) = match circles.last() {
+            None => (None, top),
+            Some(previous) if previous.column == column => {
+                row_spans.clear();
+                (
+                    Some(SegmentKind::Vertical),
+                    previous.y + spacing.vertical_arrow_spacing,
+                )
+            }
+            Some(previous) => {
+                let span = (previous.x.min(x), previous.x.max(x));
+                let overlaps = row_spans
+                    .iter()
+                    .any(|&(start, end)| span.0 < end && start < span.1);
+                if overlaps {
+                    row_spans.clear();
+                    row_spans.push(span);
+                    (Some(SegmentKind::Elbow), previous.y + spacing.item_height)
+                } else {
+                    row_spans.push(span);
+                    (Some(SegmentKind::Horizontal), previous.y)
+                }
+            }
+        };
+
+        let circle = CircleLayout {
+            column,
+            x,
+            y,
+            points: state.points,
+            points_change: circles
+                .last()
+                .map_or(0.0, |previous| state.points - previous.points),
+        };
+        if let (Some(kind), Some(previous)) = (kind, circles.last()) {
+            arrows.push(layout_arrow(kind, previous, &circle));
+        }
+        circles.push(circle);
+    }
+
+    // Label the first arrow, above it when it is horizontal or beside it when it is vertical
+    let label = match (arrows.first(), circles.first(), circles.get(1)) {
+        (Some(arrow), Some(from), Some(to)) => Some(if arrow.kind == SegmentKind::Vertical {
+            let y = (from.y + to.y) / 2.0;
+            // For the last column put the label on the left, otherwise on the right
+            if from.column == chart.columns.len() - 1 {
+                LabelLayout {
+                    x: from.x - VERTICAL_LABEL_OFFSET,
+                    y,
+                    anchor: LabelAnchor::End,
+                }
+            } else {
+                LabelLayout {
+                    x: from.x + VERTICAL_LABEL_OFFSET,
+                    y,
+                    anchor: LabelAnchor::Start,
+                }
+            }
+        } else {
+            LabelLayout {
+                x: (from.x + to.x) / 2.0,
+                y: to.y - HORIZONTAL_LABEL_OFFSET,
+                anchor: LabelAnchor::Above,
+            }
+        }),
+        _ => None,
+    };
+
+    ItemLayout {
+        item,
+        circles,
+        arrows,
+        label,
+    }
+}
+
+/// Lay out the groups of items in a panel, each labelled group gets a swimlane with room for
+/// its label above the first item
+fn layout_panel<'a>(
+    chart: &WorkItemMovement,
+    all_groups: &[WorkItemGroup],
+    groups: &[WorkItemGroup<'a>],
+    column_positions: Vec<f64>,
+    (left, right): (f64, f64),
+    items_top: f64,
+    spacing: ItemSpacing,
+) -> PanelLayout<'a> {
+    let mut items = Vec::new();
+    let mut swimlanes = Vec::new();
+    let mut next_item_y = items_top;
+    for group in groups {
+        if group.label.is_some() {
+            next_item_y += SWIMLANE_LABEL_HEIGHT;
+        }
+        let group_top = next_item_y - spacing.item_height / 2.0 - SWIMLANE_LABEL_HEIGHT;
+
+        for item in &group.items {
+            let layout = layout_item(item, chart, &column_positions, next_item_y, spacing);
+            next_item_y = layout.bottom() + spacing.item_height;
+            items.push(layout);
+        }
+
+        if let Some(ref label) = group.label {
+            // A group split across panels keeps its index so it keeps its colour
+            let group_index = all_groups
+                .iter()
+                .position(|other| other.label == group.label)
+                .unwrap_or(0);
+            swimlanes.push(SwimlaneLayout {
+                label: label.clone(),
+                group_index,
+                x: left - MARGIN / 2.0,
+                y: group_top,
+                width: right - left + MARGIN,
+                height: next_item_y - spacing.item_height / 2.0 - group_top,
+                label_x: left,
+                label_y: group_top + SWIMLANE_LABEL_HEIGHT / 2.0,
+            });
+        }
+    }
+
+    let last_item_y = items
+        .iter()
+        .map(ItemLayout::bottom)
+        .fold(items_top, f64::max);
+
+    PanelLayout {
+        column_positions,
+        column_line_top: items_top - CIRCLE_RADIUS - LINE_EXTENSION,
+        column_line_bottom: last_item_y + CIRCLE_RADIUS + LINE_EXTENSION,
+        items,
+        swimlanes,
+    }
+}
+
+/// Split the groups after the given number of items, a group that is split keeps its label in
+/// both halves
+fn split_groups<'a>(
+    groups: &[WorkItemGroup<'a>],
+    at: usize,
+) -> (Vec<WorkItemGroup<'a>>, Vec<WorkItemGroup<'a>>) {
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    let mut remaining = at;
+    for group in groups {
+        if remaining >= group.items.len() {
+            remaining -= group.items.len();
+            first.push(group.clone());
+        } else if remaining == 0 {
+            second.push(group.clone());
+        } else {
+            first.push(WorkItemGroup {
+                label: group.label.clone(),
+                items: group.items[..remaining].to_vec(),
+            });
+            second.push(WorkItemGroup {
+                label: group.label.clone(),
+                items: group.items[remaining..].to_vec(),
+            });
+            remaining = 0;
+        }
+    }
+    (first, second)
+}
+
+/// Lay out the chart within `width` and, where possible, `max_height`. When the chart is taller
+/// than the maximum height the items are moved closer together down to a minimum spacing, after
+/// which they are split across two panels side by side. The legend is laid out by the caller
+/// and placed below the items and summary.
+pub fn layout_work_item_movement<'a>(
+    chart: &'a WorkItemMovement,
+    width: u32,
+    max_height: u32,
+    font_data: &Option<Vec<u8>>,
+    legend: &LegendLayout,
+) -> WorkItemMovementLayout<'a> {
+    let width = width as f64;
+
+    // Calculate title height
+    let (title_height, title_gap) = if chart.title.is_some() {
+        let text_height = if let Some(ref font_data) = font_data {
+            measure_text_height(font_data, TITLE_FONT_SIZE as f32) as f64
+        } else {
+            TITLE_FONT_SIZE
+        };
+        (text_height, TITLE_GAP)
+    } else {
+        (0.0, 0.0)
+    };
+    let content_top = MARGIN + title_height + title_gap;
+    let items_top = content_top + COLUMN_HEIGHT + COLUMN_TO_ITEMS_GAP;
+
+    // The optional summary footer has three lines per column followed by the overall scope change
+    let summary_height = if chart.show_summary {
+        SUMMARY_GAP * 3.0 + SUMMARY_LINE_HEIGHT * 4.0
+    } else {
+        0.0
+    };
+    let legend_height = if legend.rows == 0 {
+        0.0
+    } else {
+        LEGEND_GAP + legend.height
+    };
+    let footer_height = summary_height + legend_height + MARGIN;
+
+    let groups = group_work_items(chart);
+    let column_widths = measure_column_widths(&chart.columns, font_data);
+    let full_width_positions = calculate_column_positions(&column_widths, MARGIN, width - MARGIN);
+    let panel_bounds = [
+        (MARGIN, width / 2.0 - MARGIN),
+        (width / 2.0 + MARGIN, width - MARGIN),
+    ];
+
+    let items_bottom = |panels: &[PanelLayout]| {
+        panels
+            .iter()
+            .filter(|panel| !panel.items.is_empty())
+            .map(|panel| panel.column_line_bottom)
+            .fold(items_top, f64::max)
+    };
+    let fits = |panels: &[PanelLayout]| items_bottom(panels) + footer_height <= max_height as f64;
+    let single_panel = |spacing: ItemSpacing| {
+        vec![layout_panel(
+            chart,
+            &groups,
+            &groups,
+            full_width_positions.clone(),
+            (MARGIN, width - MARGIN),
+            items_top,
+            spacing,
+        )]
+    };
+    // Split the items at the point that keeps the taller panel as short as possible
+    let two_panels = |spacing: ItemSpacing| {
+        (1..chart.items.len())
+            .map(|at| {
+                let (first, second) = split_groups(&groups, at);
+                [first, second]
+                    .iter()
+                    .zip(panel_bounds)
+                    .map(|(panel_groups, (left, right))| {
+                        layout_panel(
+                            chart,
+                            &groups,
+                            panel_groups,
+                            calculate_column_positions(&column_widths, left, right),
+                            (left, right),
+                            items_top,
+                            spacing,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .min_by(|a, b| items_bottom(a).total_cmp(&items_bottom(b)))
+            .unwrap_or_else(|| single_panel(spacing))
+    };
+
+    let spacings: Vec<ItemSpacing> = (0..=COMPACTION_STEPS)
+        .map(|step| ItemSpacing::compacted(step as f64 / COMPACTION_STEPS as f64))
+        .collect();
+    let (spacing, panels) = spacings
+        .iter()
+        .map(|spacing| (*spacing, single_panel(*spacing)))
+        .find(|(_, panels)| fits(panels))
+        .or_else(|| {
+            if chart.items.len() < 2 {
+                return None;
+            }
+            spacings
+                .iter()
+                .map(|spacing| (*spacing, two_panels(*spacing)))
+                .find(|(_, panels)| fits(panels))
+        })
+        .unwrap_or_else(|| {
+            let panels = if chart.items.len() < 2 {
+                single_panel(MIN_SPACING)
+            } else {
+                two_panels(MIN_SPACING)
+            };
+            eprintln!(
+                "Warning: Work item movement chart needs a height of {}px which is more than the maximum of {}px",
+                (items_bottom(&panels) + footer_height) as u32,
+                max_height
+            );
+            (MIN_SPACING, panels)
+        });
+
+    let items_bottom = items_bottom(&panels);
+    let summary = chart.show_summary.then(|| {
+        let top = items_bottom + SUMMARY_GAP;
+        let first_line_y = top + SUMMARY_GAP + SUMMARY_LINE_HEIGHT / 2.0;
+        SummaryLayout {
+            top,
+            column_positions: full_width_positions.clone(),
+            column_line_ys: [
+                first_line_y,
+                first_line_y + SUMMARY_LINE_HEIGHT,
+                first_line_y + SUMMARY_LINE_HEIGHT * 2.0,
+            ],
+            scope_y: first_line_y + SUMMARY_LINE_HEIGHT * 3.0 + SUMMARY_GAP,
+        }
+    });
+
+    WorkItemMovementLayout {
+        width,
+        height: (items_bottom + footer_height).floor(),
+        title_y: chart.title.as_ref().map(|_| MARGIN + title_height / 2.0),
+        column_label_y: content_top + COLUMN_HEIGHT / 2.0,
+        spacing,
+        panels,
+        summary,
+        legend_y: items_bottom + summary_height + LEGEND_GAP,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::renderer::LegendPosition;
+    use crate::work_item_movement::parse_work_item_movement;
+
+    fn chart(input: &str) -> WorkItemMovement {
+        parse_work_item_movement(input, None).unwrap().1
+    }
+
+    fn layout(chart: &WorkItemMovement, max_height: u32) -> WorkItemMovementLayout<'_> {
+        let legend = LegendLayout {
+            position: LegendPosition::Bottom,
+            columns: 0,
+            rows: 0,
+            column_width: 0.0,
+            column_major: true,
+            width: 0.0,
+            height: 0.0,
+        };
+        layout_work_item_movement(chart, 800, max_height, &None, &legend)
+    }
+
+    #[test]
+    fn test_column_matching_ignores_case() {
+        let exact = chart(
+            "work-item-movement\n  columns [To Do, Done]\n  PJ-1 To Do: 1 -> To Do: 2\n  PJ-2 Done: 1 -> Done: 2\n",
+        );
+        let mixed_case = chart(
+            "work-item-movement\n  columns [To Do, Done]\n  PJ-1 to do: 1 -> TO DO: 2\n  PJ-2 done: 1 -> Done: 2\n",
+        );
+        let exact = layout(&exact, 2000);
+        let mixed_case = layout(&mixed_case, 2000);
+
+        assert_eq!(exact.height, mixed_case.height);
+        let circles = |layout: &WorkItemMovementLayout| -> Vec<(usize, f64, f64)> {
+            layout.panels[0]
+                .items
+                .iter()
+                .flat_map(|item| item.circles.iter())
+                .map(|circle| (circle.column, circle.x, circle.y))
+                .collect()
+        };
+        assert_eq!(circles(&exact), circles(&mixed_case));
+        assert_eq!(circles(&mixed_case)[2].0, 1);
+    }
+
+    #[test]
+    fn test_layout_horizontal_and_vertical_items() {
+        let chart = chart(
+            "work-item-movement\n  columns [To Do, Done]\n  PJ-1 To Do: 1 -> Done: 2\n  PJ-2 Done: 3 -> Done: 2\n",
+        );
+        let layout = layout(&chart, 2000);
+        let panel = &layout.panels[0];
+        let (to_do_x, done_x) = (panel.column_positions[0], panel.column_positions[1]);
+        let items_top = layout.column_label_y + COLUMN_HEIGHT / 2.0 + COLUMN_TO_ITEMS_GAP;
+
+        let horizontal = &panel.items[0];
+        assert_eq!(horizontal.circles[0].y, items_top);
+        assert_eq!(horizontal.circles[1].points_change, 1.0);
+        let arrow = &horizontal.arrows[0];
+        assert_eq!(arrow.kind, SegmentKind::Horizontal);
+        assert_eq!(arrow.line[0], (to_do_x + CIRCLE_RADIUS, items_top));
+        assert_eq!(arrow.head[0], (done_x - CIRCLE_RADIUS, items_top));
+        assert_eq!(
+            horizontal.label,
+            Some(LabelLayout {
+                x: (to_do_x + done_x) / 2.0,
+                y: items_top - HORIZONTAL_LABEL_OFFSET,
+                anchor: LabelAnchor::Above,
+            })
+        );
+
+        // The vertical item starts one item height below and is labelled on the left as it is
+        // in the last column
+        let vertical = &panel.items[1];
+        let top = items_top + DEFAULT_SPACING.item_height;
+        assert_eq!(vertical.circles[0].y, top);
+        assert_eq!(
+            vertical.circles[1].y,
+            top + DEFAULT_SPACING.vertical_arrow_spacing
+        );
+        assert_eq!(vertical.arrows[0].kind, SegmentKind::Vertical);
+        assert_eq!(
+            vertical.label.as_ref().map(|label| label.anchor),
+            Some(LabelAnchor::End)
+        );
+
+        let last_circle_y = top + DEFAULT_SPACING.vertical_arrow_spacing;
+        assert_eq!(
+            panel.column_line_bottom,
+            last_circle_y + CIRCLE_RADIUS + LINE_EXTENSION
+        );
+        assert_eq!(layout.height, panel.column_line_bottom + MARGIN);
+    }
+
+    #[test]
+    fn test_backwards_move_uses_new_row() {
+        let chart = chart(
+            "work-item-movement\n  columns [To Do, In Progress, Done]\n  PJ-1 To Do: 1 -> Done: 1 -> In Progress: 1\n  PJ-2 To Do: 1 -> Done: 1\n",
+        );
+        let layout = layout(&chart, 2000);
+        let items = &layout.panels[0].items;
+        let kinds: Vec<SegmentKind> = items[0].arrows.iter().map(|arrow| arrow.kind).collect();
+        assert_eq!(kinds, vec![SegmentKind::Horizontal, SegmentKind::Elbow]);
+        assert_eq!(
+            items[0].circles[2].y,
+            items[0].circles[1].y + DEFAULT_SPACING.item_height
+        );
+        assert_eq!(
+            items[1].circles[0].y,
+            items[0].circles[2].y + DEFAULT_SPACING.item_height
+        );
+    }
+
+    #[test]
+    fn test_compaction_and_panels() {
+        let mut input = String::from("work-item-movement\n  columns [To Do, Done]\n");
+        for i in 0..10 {
+            input.push_str(&format!("  PJ-{} To Do: 1 -> Done: 1\n", i));
+        }
+
+        let chart = chart(&input);
+
+        let roomy = layout(&chart, 2000);
+        assert_eq!(roomy.spacing, DEFAULT_SPACING);
+        assert_eq!(roomy.panels.len(), 1);
+
+        let compact = layout(&chart, 500);
+        assert!(compact.height <= 500.0);
+        assert!(compact.spacing.item_height < DEFAULT_SPACING.item_height);
+        assert_eq!(compact.panels.len(), 1);
+
+        let two_up = layout(&chart, 350);
+        assert!(two_up.height <= 350.0);
+        assert_eq!(two_up.panels.len(), 2);
+        assert_eq!(two_up.panels[0].items.len(), 5);
+        assert_eq!(two_up.panels[1].items.len(), 5);
+    }
+}
@@ -1,30 +1,109 @@
 use crate::common::ChartConfig;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct WorkItemMovement {
     pub config: Option<ChartConfig>,
     pub title: Option<String>,
+    /// Draw a footer with the points that entered and left each column, from `showSummary`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub show_summary: bool,
     pub columns: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub items: Vec<WorkItem>,
+    /// The order of the items within each group, from `sort by`, otherwise the file order is used
+    pub sort_by: Option<SortBy>,
+    /// How the items are grouped into swimlanes, from `group by`, otherwise items are grouped by
+    /// their `section`
+    pub group_by: Option<GroupBy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SortBy {
+    /// The column the item finished in, from the first column to the last
+    Column,
+    /// The points the item finished with, from the most points to the least
+    Points,
+    /// The id, comparing any number at the end of the id numerically so `PJ-2` is before `PJ-10`
+    Id,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum GroupBy {
+    /// The id without the number at the end, e.g. the Jira project key `PJ` of `PJ-12`
+    Prefix,
+    /// The column the item finished in
+    Column,
+}
+
+impl WorkItemMovement {
+    /// Find the column matching a state name, ignoring case
+    pub fn column_index(&self, state: &str) -> Option<usize> {
+        let state = state.to_lowercase();
+        self.columns
+            .iter()
+            .position(|column| column.to_lowercase() == state)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct WorkItemState {
+    pub name: String,
+    pub points: f64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct WorkItem {
     pub id: String,
-    pub from_state: String,
-    pub from_points: f64,
-    pub to_state: String,
-    pub to_points: f64,
+    pub title: Option<String>,
+    /// The `section` the item was listed under
+    pub section: Option<String>,
+    /// The states the item moved through in order, parsed items have at least two states
+    pub states: Vec<WorkItemState>,
 }
 
 impl WorkItem {
+    pub fn from_state(&self) -> &str {
+        self.states.first().map_or("", |state| &state.name)
+    }
+
+    pub fn from_points(&self) -> f64 {
+        self.states.first().map_or(0.0, |state| state.points)
+    }
+
+    pub fn to_state(&self) -> &str {
+        self.states.last().map_or("", |state| &state.name)
+    }
+
+    pub fn to_points(&self) -> f64 {
+        self.states.last().map_or(0.0, |state| state.points)
+    }
+
     pub fn points_change(&self) -> f64 {
-        self.to_points - self.from_points
+        self.to_points() - self.from_points()
     }
 }
 
+pub mod grouping;
+pub mod jira;
+pub mod layout;
 pub mod parser;
 pub mod renderer;
+pub mod summary;
+pub mod writer;
 
+pub use grouping::*;
+pub use jira::*;
+pub use layout::*;
 pub use parser::*;
 pub use renderer::*;
+pub use summary::*;
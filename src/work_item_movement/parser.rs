@@ -1,15 +1,16 @@
 use nom::{
-    bytes::complete::{tag, take_until, take_while1},
-    character::complete::{char, digit1, multispace0, space0},
-    combinator::{map, opt, recognize},
-    multi::separated_list0,
-    sequence::{delimited, tuple},
+    branch::alt,
+    bytes::complete::{tag, take_till1, take_until, take_while1},
+    character::complete::{char, digit1, line_ending, multispace0, space0, space1},
+    combinator::{eof, map, not, opt, peek, recognize},
+    multi::{many1, separated_list0},
+    sequence::{pair, preceded, terminated, tuple},
     IResult,
 };
 use std::fmt;
 
-use super::{WorkItem, WorkItemMovement};
-use crate::common::ChartConfig;
+use super::{GroupBy, SortBy, WorkItem, WorkItemMovement, WorkItemState};
+use crate::common::{string_parser::any_quoted_string, ChartConfig};
 
 #[derive(Debug)]
 pub struct ValidationError {
@@ -24,21 +25,30 @@ impl fmt::Display for ValidationError {
 
 impl std::error::Error for ValidationError {}
 
-fn header(input: &str) -> IResult<&str, ()> {
+/// Parse the header, returning whether `showSummary` follows it
+fn header(input: &str) -> IResult<&str, bool> {
     let (input, _) = tag("work-item-movement")(input)?;
-    Ok((input, ()))
-}
-
-fn quoted_string(input: &str) -> IResult<&str, &str> {
-    delimited(char('\''), take_until("'"), char('\''))(input)
+    let (input, _) = space0(input)?;
+    let (input, show_summary) = opt(tag("showSummary"))(input)?;
+    Ok((input, show_summary.is_some()))
 }
 
 fn title_line(input: &str) -> IResult<&str, Option<String>> {
     let (input, _) = multispace0(input)?;
     let (input, _) = tag("title")(input)?;
     let (input, _) = space0(input)?;
-    let (input, title) = quoted_string(input)?;
-    Ok((input, Some(title.to_string())))
+    let (input, title) = any_quoted_string(input)?;
+    Ok((input, Some(title)))
+}
+
+/// Parse a column name, which is quoted when it contains `,` or `]`
+fn column_name(input: &str) -> IResult<&str, String> {
+    alt((
+        terminated(any_quoted_string, space0),
+        map(take_while1(|c: char| c != ',' && c != ']'), |s: &str| {
+            s.trim().to_string()
+        }),
+    ))(input)
 }
 
 fn columns_line(input: &str) -> IResult<&str, Vec<String>> {
@@ -46,77 +56,160 @@ fn columns_line(input: &str) -> IResult<&str, Vec<String>> {
     let (input, _) = tag("columns")(input)?;
     let (input, _) = space0(input)?;
     let (input, _) = char('[')(input)?;
-    let (input, columns) = separated_list0(
-        tuple((space0, char(','), space0)),
-        map(take_while1(|c: char| c != ',' && c != ']'), |s: &str| {
-            s.trim().to_string()
-        }),
-    )(input)?;
+    let (input, columns) = separated_list0(tuple((space0, char(','), space0)), column_name)(input)?;
     let (input, _) = char(']')(input)?;
     Ok((input, columns))
 }
 
 fn number(input: &str) -> IResult<&str, f64> {
     map(
-        recognize(tuple((
-            digit1,
-            opt(tuple((char('.'), digit1))),
-        ))),
+        recognize(tuple((digit1, opt(tuple((char('.'), digit1)))))),
         |s: &str| s.parse().unwrap(),
     )(input)
 }
 
-fn work_item_id(input: &str) -> IResult<&str, &str> {
-    recognize(tuple((
-        take_while1(|c: char| c.is_alphabetic()),
-        char('-'),
-        digit1,
-    )))(input)
+/// An id is a quoted string or any token without whitespace, e.g. `PJ-1`, `AB2-17` or `#123`
+fn work_item_id(input: &str) -> IResult<&str, String> {
+    alt((
+        any_quoted_string,
+        map(take_till1(|c: char| c.is_whitespace()), str::to_string),
+    ))(input)
 }
 
-fn state_with_points(input: &str) -> IResult<&str, (&str, f64)> {
-    let (input, state) = take_until(":")(input)?;
+/// Parse a state such as `In Progress: 2`, the name is quoted when it contains `:` or `->`
+fn state_with_points(input: &str) -> IResult<&str, WorkItemState> {
+    let (input, name) = alt((
+        terminated(any_quoted_string, space0),
+        map(take_until(":"), |s: &str| s.trim().to_string()),
+    ))(input)?;
     let (input, _) = char(':')(input)?;
     let (input, _) = space0(input)?;
     let (input, points) = number(input)?;
-    Ok((input, (state.trim(), points)))
+    Ok((input, WorkItemState { name, points }))
 }
 
+/// Parse an item's history, e.g. `PJ-1 To Do: 1 -> In Progress: 2 -> Done: 3`
 fn work_item_line(input: &str) -> IResult<&str, WorkItem> {
     let (input, _) = multispace0(input)?;
     let (input, id) = work_item_id(input)?;
     let (input, _) = space0(input)?;
-    let (input, (from_state, from_points)) = state_with_points(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = tag("->")(input)?;
+    // A quoted string followed by `:` is the name of the first state rather than the title
+    let (input, title) = opt(terminated(any_quoted_string, pair(space0, not(char(':')))))(input)?;
     let (input, _) = space0(input)?;
-    let (input, (to_state, to_points)) = state_with_points(input)?;
+    let (input, first_state) = state_with_points(input)?;
+    let (input, next_states) = many1(preceded(
+        tuple((space0, tag("->"), space0)),
+        state_with_points,
+    ))(input)?;
+
+    let mut states = vec![first_state];
+    states.extend(next_states);
 
     Ok((
         input,
         WorkItem {
-            id: id.to_string(),
-            from_state: from_state.to_string(),
-            from_points,
-            to_state: to_state.to_string(),
-            to_points,
+            id,
+            title,
+            section: None,
+            states,
         },
     ))
 }
 
+/// Error for an unknown `sort by` or `group by` option, this fails rather than errors so the
+/// directive isn't parsed as a work item
+fn invalid_option(input: &str) -> nom::Err<nom::error::Error<&str>> {
+    nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Verify))
+}
+
+/// Parse the option of a directive such as `sort by points`, which must end the line
+fn directive<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    move |input: &'a str| {
+        let (input, _) = tuple((tag(keyword), space1, tag("by"), space1))(input)?;
+        terminated(
+            take_till1(|c: char| c.is_whitespace()),
+            pair(space0, peek(alt((line_ending, eof)))),
+        )(input)
+    }
+}
+
+fn sort_line(input: &str) -> IResult<&str, SortBy> {
+    let (rest, option) = directive("sort")(input)?;
+    let sort_by = match option {
+        "column" => SortBy::Column,
+        "points" => SortBy::Points,
+        "id" => SortBy::Id,
+        _ => return Err(invalid_option(input)),
+    };
+    Ok((rest, sort_by))
+}
+
+fn group_line(input: &str) -> IResult<&str, GroupBy> {
+    let (rest, option) = directive("group")(input)?;
+    let group_by = match option {
+        "prefix" => GroupBy::Prefix,
+        "column" => GroupBy::Column,
+        _ => return Err(invalid_option(input)),
+    };
+    Ok((rest, group_by))
+}
+
+/// Parse `section 'Team A'`, the items that follow it belong to the section
+fn section_line(input: &str) -> IResult<&str, String> {
+    let (input, _) = tuple((tag("section"), space1))(input)?;
+    terminated(
+        any_quoted_string,
+        pair(space0, peek(alt((line_ending, eof)))),
+    )(input)
+}
+
+/// The statements that can follow the columns
+enum Statement {
+    Item(WorkItem),
+    Section(String),
+    Sort(SortBy),
+    Group(GroupBy),
+}
+
+fn statement(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = multispace0(input)?;
+    alt((
+        map(sort_line, Statement::Sort),
+        map(group_line, Statement::Group),
+        map(section_line, Statement::Section),
+        map(work_item_line, Statement::Item),
+    ))(input)
+}
+
 pub fn parse_work_item_movement(
     input: &str,
     config: Option<ChartConfig>,
 ) -> IResult<&str, WorkItemMovement> {
-    let (input, _) = header(input)?;
+    let (input, show_summary) = header(input)?;
     let (input, _) = multispace0(input)?;
     let (input, title) = opt(title_line)(input)?;
     let (input, _) = multispace0(input)?;
     let (input, columns) = columns_line(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, items) = separated_list0(multispace0, work_item_line)(input)?;
+    let (input, statements) = separated_list0(multispace0, statement)(input)?;
     let (input, _) = multispace0(input)?;
 
+    let mut items = Vec::new();
+    let mut section = None;
+    let mut sort_by = None;
+    let mut group_by = None;
+    for statement in statements {
+        match statement {
+            Statement::Item(mut item) => {
+                item.section = section.clone();
+                items.push(item);
+            }
+            Statement::Section(name) => section = Some(name),
+            Statement::Sort(option) => sort_by = Some(option),
+            Statement::Group(option) => group_by = Some(option),
+        }
+    }
+
     // Don't validate here - we'll validate in a separate function
 
     Ok((
@@ -124,40 +217,40 @@ pub fn parse_work_item_movement(
         WorkItemMovement {
             config,
             title: title.flatten(),
+            show_summary,
             columns,
             items,
+            sort_by,
+            group_by,
         },
     ))
 }
 
-/// Validates that all referenced states in work items exist in the columns list
+/// Validates that every work item has at least two states and that they all exist in the
+/// columns list
 pub fn validate_work_item_movement(chart: &WorkItemMovement) -> Result<(), ValidationError> {
     for item in &chart.items {
-        // Case-insensitive check for from_state
-        if !chart
-            .columns
-            .iter()
-            .any(|col| col.to_lowercase() == item.from_state.to_lowercase())
-        {
+        // Parsed items always have two states but charts built in code may not
+        if item.states.len() < 2 {
             return Err(ValidationError {
                 message: format!(
-                    "Work item '{}' references column '{}' which does not exist. Available columns are: {:?}",
-                    item.id, item.from_state, chart.columns
+                    "Work item '{}' needs at least two states but has {}",
+                    item.id,
+                    item.states.len()
                 ),
             });
         }
-        // Case-insensitive check for to_state
-        if !chart
-            .columns
-            .iter()
-            .any(|col| col.to_lowercase() == item.to_state.to_lowercase())
-        {
-            return Err(ValidationError {
-                message: format!(
-                    "Work item '{}' references column '{}' which does not exist. Available columns are: {:?}",
-                    item.id, item.to_state, chart.columns
-                ),
-            });
+
+        // Case-insensitive check for every state the item moved through
+        for state in &item.states {
+            if chart.column_index(&state.name).is_none() {
+                return Err(ValidationError {
+                    message: format!(
+                        "Work item '{}' references column '{}' which does not exist. Available columns are: {:?}",
+                        item.id, state.name, chart.columns
+                    ),
+                });
+            }
         }
     }
     Ok(())
@@ -202,18 +295,145 @@ mod tests {
 
         let item = &chart.items[0];
         assert_eq!(item.id, "PJ-633");
-        assert_eq!(item.from_state, "Not Existing");
-        assert_eq!(item.from_points, 0.0);
-        assert_eq!(item.to_state, "Draft");
-        assert_eq!(item.to_points, 1.0);
+        assert_eq!(item.from_state(), "Not Existing");
+        assert_eq!(item.from_points(), 0.0);
+        assert_eq!(item.to_state(), "Draft");
+        assert_eq!(item.to_points(), 1.0);
         assert_eq!(item.points_change(), 1.0);
 
         let item = &chart.items[2];
         assert_eq!(item.id, "PJ-1");
-        assert_eq!(item.from_state, "In Progress");
-        assert_eq!(item.from_points, 5.0);
-        assert_eq!(item.to_state, "Draft");
-        assert_eq!(item.to_points, 8.0);
+        assert_eq!(item.from_state(), "In Progress");
+        assert_eq!(item.from_points(), 5.0);
+        assert_eq!(item.to_state(), "Draft");
+        assert_eq!(item.to_points(), 8.0);
         assert_eq!(item.points_change(), 3.0);
     }
+
+    #[test]
+    fn test_parse_work_item_ids_and_titles() {
+        let input = r#"work-item-movement
+  columns [To Do, In Review, Done]
+  AB2-17 To Do: 1 -> Done: 1
+  X_Y-3 "Login page" In Review: 3 -> Done: 3
+  #123 'Fix the build' To Do: 2 -> In Review: 2
+  "550e8400 e29b" To Do: 5 -> To Do: 8
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        assert_eq!(chart.items.len(), 4);
+
+        assert_eq!(chart.items[0].id, "AB2-17");
+        assert_eq!(chart.items[0].title, None);
+        assert_eq!(chart.items[0].from_state(), "To Do");
+
+        assert_eq!(chart.items[1].id, "X_Y-3");
+        assert_eq!(chart.items[1].title, Some("Login page".to_string()));
+        assert_eq!(chart.items[1].from_state(), "In Review");
+
+        assert_eq!(chart.items[2].id, "#123");
+        assert_eq!(chart.items[2].title, Some("Fix the build".to_string()));
+        assert_eq!(chart.items[2].to_state(), "In Review");
+
+        assert_eq!(chart.items[3].id, "550e8400 e29b");
+        assert_eq!(chart.items[3].title, None);
+        assert_eq!(chart.items[3].to_points(), 8.0);
+    }
+
+    #[test]
+    fn test_parse_multi_hop_work_item() {
+        let input = r#"work-item-movement
+  columns [To Do, In Progress, In Review, Done]
+  PJ-7 To Do: 1 -> In Progress: 2 -> In Review: 2 -> In Progress: 3
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        assert_eq!(chart.items.len(), 1);
+
+        let item = &chart.items[0];
+        assert_eq!(
+            item.states,
+            vec![
+                WorkItemState {
+                    name: "To Do".to_string(),
+                    points: 1.0
+                },
+                WorkItemState {
+                    name: "In Progress".to_string(),
+                    points: 2.0
+                },
+                WorkItemState {
+                    name: "In Review".to_string(),
+                    points: 2.0
+                },
+                WorkItemState {
+                    name: "In Progress".to_string(),
+                    points: 3.0
+                },
+            ]
+        );
+        assert_eq!(item.from_state(), "To Do");
+        assert_eq!(item.to_state(), "In Progress");
+        assert_eq!(item.points_change(), 2.0);
+        assert!(validate_work_item_movement(&chart).is_ok());
+    }
+
+    #[test]
+    fn test_validate_unknown_state_in_history() {
+        let input = r#"work-item-movement
+  columns [To Do, Done]
+  PJ-7 To Do: 1 -> Blocked: 1 -> Done: 1
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        let error = validate_work_item_movement(&chart).unwrap_err();
+        assert!(error.message.contains("'Blocked'"));
+    }
+
+    #[test]
+    fn test_parse_sections_and_directives() {
+        let input = r#"work-item-movement
+  columns [To Do, Done]
+  sort by id
+  group by prefix
+  PJ-1 To Do: 1 -> Done: 1
+  section 'Team A'
+  PJ-2 To Do: 2 -> Done: 2
+  section "Team B"
+  sort "Sort the list" To Do: 3 -> Done: 3
+"#;
+
+        let (remaining, chart) = parse_work_item_movement(input, None).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(chart.sort_by, Some(SortBy::Id));
+        assert_eq!(chart.group_by, Some(GroupBy::Prefix));
+
+        let sections: Vec<_> = chart
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item.section.as_deref()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("PJ-1", None),
+                ("PJ-2", Some("Team A")),
+                ("sort", Some("Team B"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_sort_option() {
+        let input = r#"work-item-movement
+  columns [To Do, Done]
+  sort by size
+  PJ-1 To Do: 1 -> Done: 1
+"#;
+
+        assert!(matches!(
+            parse_work_item_movement(input, None),
+            Err(nom::Err::Failure(_))
+        ));
+    }
 }
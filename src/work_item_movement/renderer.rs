@@ -1,5 +1,10 @@
-use super::WorkItemMovement;
-use crate::font::{load_system_font_bytes, measure_text_height, measure_text_width};
+use super::layout::{
+    layout_work_item_movement, LabelAnchor, CIRCLE_FONT_SIZE, CIRCLE_RADIUS, COLUMN_FONT_SIZE,
+    ITEM_FONT_SIZE, MARGIN, SUMMARY_FONT_SIZE, TITLE_FONT_SIZE,
+};
+use super::{summarise_work_item_movement, WorkItem, WorkItemMovement, WorkItemState};
+use crate::common::renderer::{layout_legend, render_legend, LegendConfig, LegendPosition};
+use crate::font::load_system_font_bytes;
 use svg::node::element::{Circle, Group, Line, Path, Rectangle, Style, Text};
 use svg::Document;
 
@@ -11,9 +16,155 @@ fn format_points(points: f64) -> String {
     }
 }
 
+/// Format a change in points with an explicit sign
+fn format_points_change(points: f64) -> String {
+    if points > 0.0 {
+        format!("+{}", format_points(points))
+    } else {
+        format_points(points)
+    }
+}
+
+/// The label drawn next to an item's arrow, the id and title followed by any change in points
+fn item_label(item: &WorkItem) -> String {
+    let mut label_text = match item.title {
+        Some(ref title) => format!("{} {}", item.id, title),
+        None => item.id.clone(),
+    };
+    let points_change = item.points_change();
+    if points_change != 0.0 {
+        label_text.push_str(&format!(": {}", format_points_change(points_change)));
+    }
+    label_text
+}
+
+/// The colour coding of arrows and circles, each arrow is coloured by how the item moved between
+/// the columns it connects and each circle by how the points changed from the previous state
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChangeType {
+    /// Moved out of the first column, such as an item added to the sprint
+    New,
+    Forward,
+    /// Moved to an earlier column
+    Regression,
+    ScopeIncrease,
+    ScopeDecrease,
+    Unchanged,
+}
+
+impl ChangeType {
+    /// All change types in the order they are shown in the legend
+    const ALL: [ChangeType; 6] = [
+        ChangeType::New,
+        ChangeType::Forward,
+        ChangeType::Regression,
+        ChangeType::ScopeIncrease,
+        ChangeType::ScopeDecrease,
+        ChangeType::Unchanged,
+    ];
+
+    fn class_name(&self) -> &'static str {
+        match self {
+            ChangeType::New => "new",
+            ChangeType::Forward => "forward",
+            ChangeType::Regression => "regression",
+            ChangeType::ScopeIncrease => "scope-increase",
+            ChangeType::ScopeDecrease => "scope-decrease",
+            ChangeType::Unchanged => "unchanged",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ChangeType::New => "New",
+            ChangeType::Forward => "Forward",
+            ChangeType::Regression => "Regression",
+            ChangeType::ScopeIncrease => "Scope increase",
+            ChangeType::ScopeDecrease => "Scope decrease",
+            ChangeType::Unchanged => "Unchanged",
+        }
+    }
+
+    /// The theme variable holding the colour along with the default colour
+    fn theme_variable(&self) -> (&'static str, &'static str) {
+        match self {
+            ChangeType::New => ("workItemMovement.newColor", "#1f77b4"),
+            ChangeType::Forward => ("workItemMovement.forwardColor", "#2ca02c"),
+            ChangeType::Regression => ("workItemMovement.regressionColor", "#d62728"),
+            ChangeType::ScopeIncrease => ("workItemMovement.scopeIncreaseColor", "#ff7f0e"),
+            ChangeType::ScopeDecrease => ("workItemMovement.scopeDecreaseColor", "#9467bd"),
+            ChangeType::Unchanged => ("workItemMovement.unchangedColor", "#131300"),
+        }
+    }
+}
+
+/// Classify an arrow by the columns of the circles it connects
+fn hop_change_type(from_column: usize, to_column: usize) -> ChangeType {
+    if from_column == to_column {
+        ChangeType::Unchanged
+    } else if from_column == 0 {
+        ChangeType::New
+    } else if to_column > from_column {
+        ChangeType::Forward
+    } else {
+        ChangeType::Regression
+    }
+}
+
+/// Classify a circle by the change in points from the previous state
+fn points_change_type(points_change: f64) -> ChangeType {
+    if points_change > 0.0 {
+        ChangeType::ScopeIncrease
+    } else if points_change < 0.0 {
+        ChangeType::ScopeDecrease
+    } else {
+        ChangeType::Unchanged
+    }
+}
+
+/// The change types of every arrow and circle in the chart in legend order
+fn used_change_types(chart: &WorkItemMovement) -> Vec<ChangeType> {
+    let used: Vec<ChangeType> = chart
+        .items
+        .iter()
+        .flat_map(|item| item.states.windows(2))
+        .flat_map(|states| {
+            let column = |state: &WorkItemState| chart.column_index(&state.name).unwrap_or(0);
+            [
+                hop_change_type(column(&states[0]), column(&states[1])),
+                points_change_type(states[1].points - states[0].points),
+            ]
+        })
+        .collect();
+    ChangeType::ALL
+        .into_iter()
+        .filter(|change_type| used.contains(change_type))
+        .collect()
+}
+
+fn get_theme_variable<'a>(chart: &'a WorkItemMovement, key: &str, default: &'a str) -> &'a str {
+    if let Some(config) = &chart.config {
+        if let Some(value) = config.theme_variables.get(key) {
+            return value;
+        }
+    }
+    default
+}
+
+/// Join points into the points of an SVG path
+fn path_data(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| format!("{} {} {}", if i == 0 { "M" } else { "L" }, x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub fn render_work_item_movement_svg(
     chart: &WorkItemMovement,
     default_width: u32,
+    max_height: u32,
     font_name: &str,
 ) -> (Document, u32, u32) {
     // Use config width if present, otherwise use default
@@ -24,106 +175,38 @@ pub fn render_work_item_movement_svg(
         .unwrap_or(default_width);
     let font_data = load_system_font_bytes(font_name);
 
-    // Layout constants
-    let margin = 20.0;
-    let title_font_size = 20.0;
-    let column_font_size = 16.0;
-    let item_font_size = 14.0;
-    let column_height = 40.0;
-    let item_height = 50.0;
-    let circle_radius = 15.0;
-    let arrow_size = 12.0;
-    let vertical_label_offset = 5.0; // Distance from line to start of text for vertical arrows
-
-    // Calculate title height
-    let (title_height, title_gap) = if chart.title.is_some() {
-        let text_height = if let Some(ref font_data) = font_data {
-            measure_text_height(font_data, title_font_size) as f64
+    // Classify the items and show the change types that are used in a legend below the chart
+    let legend_entries: Vec<ChangeType> =
+        if get_theme_variable(chart, "workItemMovement.showLegend", "true") == "true" {
+            used_change_types(chart)
         } else {
-            title_font_size as f64
+            Vec::new()
         };
-        (text_height, 20.0)
-    } else {
-        (0.0, 0.0)
-    };
-
-    // Measure column text widths to calculate proper positioning
-    let column_widths: Vec<f64> = chart
-        .columns
+    let legend_labels: Vec<String> = legend_entries
         .iter()
-        .map(|col| {
-            if let Some(ref font_data) = font_data {
-                measure_text_width(col, font_data, column_font_size) as f64
-            } else {
-                col.len() as f64 * 8.0
-            }
-        })
+        .map(|change_type| change_type.label().to_string())
         .collect();
-
-    // Calculate column positions based on vertical line placement
-    let num_columns = chart.columns.len();
-
-    let column_positions: Vec<f64> = if num_columns == 1 {
-        vec![width as f64 / 2.0]
-    } else {
-        // Calculate the exact positions for first and last lines
-        let first_line_pos = margin + column_widths[0] / 2.0;
-        let last_line_pos = width as f64 - margin - column_widths[num_columns - 1] / 2.0;
-
-        // Create positions array with first and last fixed, middle distributed evenly
-        let mut positions = vec![0.0; num_columns];
-        positions[0] = first_line_pos;
-        positions[num_columns - 1] = last_line_pos;
-
-        // Distribute middle positions evenly between first and last
-        if num_columns > 2 {
-            let spacing = (last_line_pos - first_line_pos) / (num_columns - 1) as f64;
-            for i in 1..num_columns - 1 {
-                positions[i] = first_line_pos + i as f64 * spacing;
-            }
-        }
-
-        positions
+    let legend_config = LegendConfig {
+        font_name: font_name.to_string(),
+        font_size: ITEM_FONT_SIZE,
+        icon_width: ITEM_FONT_SIZE,
+        icon_height: ITEM_FONT_SIZE,
+        item_spacing: ITEM_FONT_SIZE + 6.0,
+        draw_border: false,
+        ..Default::default()
     };
+    let legend_layout = layout_legend(
+        &legend_labels,
+        &font_data,
+        &legend_config,
+        LegendPosition::Bottom,
+        width as f64 - MARGIN * 2.0,
+        f64::MAX,
+    );
 
-    // Calculate height needed - account for vertical arrows that need extra space
-    let content_top = margin + title_height + title_gap;
-    let items_top = content_top + column_height + 20.0;
-
-    // Calculate total height needed, accounting for vertical arrows
-    let line_extension = 15.0; // Must match the line_extension used for column lines
-    let vertical_arrow_spacing = 80.0; // Space between circles in vertical arrows
-
-    let height = if chart.items.is_empty() {
-        (items_top + margin) as u32
-    } else {
-        // Calculate total height by simulating the same logic as rendering
-        let mut calc_y = items_top;
-        for item in &chart.items {
-            let from_idx = chart
-                .columns
-                .iter()
-                .position(|c| c == &item.from_state)
-                .unwrap_or(0);
-            let to_idx = chart
-                .columns
-                .iter()
-                .position(|c| c == &item.to_state)
-                .unwrap_or(0);
-
-            if from_idx == to_idx {
-                // Vertical arrow takes more space
-                calc_y += vertical_arrow_spacing + item_height;
-            } else {
-                // Regular horizontal arrow
-                calc_y += item_height;
-            }
-        }
-        // Subtract one item_height since we added it for the last item
-        // Then add circle radius and margin
-        let final_y = calc_y - item_height + circle_radius;
-        (final_y + line_extension + margin) as u32
-    };
+    // Every position is calculated up front so the size and drawing always agree
+    let layout = layout_work_item_movement(chart, width, max_height, &font_data, &legend_layout);
+    let height = layout.height as u32;
 
     // Create SVG document
     let mut document = Document::new()
@@ -137,7 +220,19 @@ pub fn render_work_item_movement_svg(
         );
 
     // Add CSS styles
-    let style = Style::new(&format!(
+    let change_type_styles: String = ChangeType::ALL
+        .iter()
+        .map(|change_type| {
+            let (key, default) = change_type.theme_variable();
+            let color = get_theme_variable(chart, key, default);
+            format!(
+                "\n            .item-arrow.{0} {{ stroke: {1}; }}\n            .arrow-head.{0}, .item-circle.{0} {{ fill: {1}; }}",
+                change_type.class_name(),
+                color
+            )
+        })
+        .collect();
+    let style = Style::new(format!(
         r#"
             .chart-title {{ text-anchor: middle; font-size: {}px; fill: #131300; font-family: "{}", sans-serif; }}
             .column-label {{ font-size: {}px; fill: #131300; font-family: "{}", sans-serif; text-anchor: middle; }}
@@ -146,15 +241,26 @@ pub fn render_work_item_movement_svg(
             .item-circle {{ fill: #131300; }}
             .item-arrow {{ stroke: #131300; stroke-width: 1px; fill: none; }}
             .arrow-head {{ fill: #131300; }}
-            .circle-text {{ fill: white; font-size: {}px; font-family: \"{}\", sans-serif; text-anchor: middle; dominant-baseline: middle; font-weight: bold; }}
+            .circle-text {{ fill: white; font-size: {}px; font-family: \"{}\", sans-serif; text-anchor: middle; dominant-baseline: middle; font-weight: bold; }}{}
+            .summary-line {{ stroke: #131300; stroke-width: 1px; }}
+            .summary-text {{ font-size: {}px; fill: #131300; font-family: "{}", sans-serif; text-anchor: middle; dominant-baseline: middle; }}
+            .summary-net {{ font-weight: bold; }}
+            .swimlane {{ fill: #f3f5f8; }}
+            .swimlane-alternate {{ fill: #e6ebf2; }}
+            .swimlane-label {{ font-size: {}px; fill: #131300; font-family: "{}", sans-serif; font-weight: bold; dominant-baseline: middle; }}
         "#,
-        title_font_size,
+        TITLE_FONT_SIZE,
+        font_name,
+        COLUMN_FONT_SIZE,
+        font_name,
+        ITEM_FONT_SIZE,
         font_name,
-        column_font_size,
+        CIRCLE_FONT_SIZE,
         font_name,
-        item_font_size,
+        change_type_styles,
+        SUMMARY_FONT_SIZE,
         font_name,
-        16.0,
+        ITEM_FONT_SIZE,
         font_name
     ));
     document = document.add(style);
@@ -171,8 +277,7 @@ pub fn render_work_item_movement_svg(
     let mut main_group = Group::new().set("class", "main");
 
     // Title
-    if let Some(title) = &chart.title {
-        let title_y = margin + title_height / 2.0;
+    if let (Some(title), Some(title_y)) = (&chart.title, layout.title_y) {
         main_group = main_group.add(
             Text::new(title)
                 .set("class", "chart-title")
@@ -183,322 +288,281 @@ pub fn render_work_item_movement_svg(
         );
     }
 
-    // Column labels and lines
-    for (i, column) in chart.columns.iter().enumerate() {
-        let x = column_positions[i];
-
-        // Column label
-        main_group = main_group.add(
-            Text::new(column)
-                .set("class", "column-label")
-                .set("x", x)
-                .set("y", content_top + column_height / 2.0),
-        );
-
-        // Vertical line
-        // Calculate where the first and last items would be
-        let line_extension = 15.0; // Extra pixels above/below circles
-        let first_item_y = items_top;
-
-        // Calculate the actual last item position accounting for vertical arrows
-        let last_item_y = if chart.items.is_empty() {
-            first_item_y
-        } else {
-            let mut calc_y = items_top;
-            for item in &chart.items {
-                let from_idx = chart
-                    .columns
-                    .iter()
-                    .position(|c| c == &item.from_state)
-                    .unwrap_or(0);
-                let to_idx = chart
-                    .columns
-                    .iter()
-                    .position(|c| c == &item.to_state)
-                    .unwrap_or(0);
-
-                if from_idx == to_idx {
-                    calc_y += vertical_arrow_spacing;
-                }
-                calc_y += item_height;
-            }
-            calc_y - item_height // Subtract the last increment
-        };
-
-        main_group = main_group.add(
-            Line::new()
-                .set("class", "column-line")
-                .set("x1", x)
-                .set("y1", first_item_y - circle_radius - line_extension)
-                .set("x2", x)
-                .set("y2", last_item_y + circle_radius + line_extension),
-        );
-    }
-
-    // Work items - calculate Y positions accounting for vertical arrows
-    let mut current_y = items_top;
-
-    for (_item_idx, item) in chart.items.iter().enumerate() {
-        let y = current_y;
-
-        // Find column indices
-        let from_idx = chart
-            .columns
-            .iter()
-            .position(|c| c.to_lowercase() == item.from_state.to_lowercase())
-            .unwrap_or(0);
-        let to_idx = chart
-            .columns
-            .iter()
-            .position(|c| c.to_lowercase() == item.to_state.to_lowercase())
-            .unwrap_or(0);
-
-        let from_x = column_positions[from_idx];
-        let to_x = column_positions[to_idx];
-
-        if from_idx == to_idx {
-            // Same column - draw vertical arrow
-            let x = from_x;
-
-            // Draw circle at start (top)
+    for panel in &layout.panels {
+        // Swimlanes are drawn first so the column lines and items are drawn over them
+        for swimlane in &panel.swimlanes {
             main_group = main_group.add(
-                Circle::new()
-                    .set("class", "item-circle")
-                    .set("cx", x)
-                    .set("cy", y)
-                    .set("r", circle_radius),
+                Rectangle::new()
+                    .set(
+                        "class",
+                        if swimlane.group_index % 2 == 0 {
+                            "swimlane"
+                        } else {
+                            "swimlane-alternate"
+                        },
+                    )
+                    .set("x", swimlane.x)
+                    .set("y", swimlane.y)
+                    .set("width", swimlane.width)
+                    .set("height", swimlane.height),
             );
-
-            // Add from points text in start circle
             main_group = main_group.add(
-                Text::new(&format_points(item.from_points))
-                    .set("class", "circle-text")
-                    .set("x", x)
-                    .set("y", y)
-                    .set("text-anchor", "middle")
-                    .set("dominant-baseline", "middle"),
+                Text::new(swimlane.label.as_str())
+                    .set("class", "swimlane-label")
+                    .set("x", swimlane.label_x)
+                    .set("y", swimlane.label_y),
             );
+        }
 
-            // Draw circle at end (bottom)
-            let end_y = y + vertical_arrow_spacing; // Use longer spacing
+        // Column labels and lines
+        for (column, x) in chart.columns.iter().zip(&panel.column_positions) {
             main_group = main_group.add(
-                Circle::new()
-                    .set("class", "item-circle")
-                    .set("cx", x)
-                    .set("cy", end_y)
-                    .set("r", circle_radius),
+                Text::new(column)
+                    .set("class", "column-label")
+                    .set("x", *x)
+                    .set("y", layout.column_label_y),
             );
 
-            // Add to points text in end circle
-            main_group = main_group.add(
-                Text::new(&format_points(item.to_points))
-                    .set("class", "circle-text")
-                    .set("x", x)
-                    .set("y", end_y)
-                    .set("text-anchor", "middle")
-                    .set("dominant-baseline", "middle"),
-            );
-
-            // Draw vertical arrow line
-            let arrow_start_y = y + circle_radius;
-            let arrow_end_y = end_y - circle_radius - arrow_size;
-
             main_group = main_group.add(
                 Line::new()
-                    .set("class", "item-arrow")
-                    .set("x1", x)
-                    .set("y1", arrow_start_y)
-                    .set("x2", x)
-                    .set("y2", arrow_end_y),
-            );
-
-            // Draw downward arrow head
-            let arrow_tip_y = end_y - circle_radius;
-            let arrow_points = format!(
-                "{},{} {},{} {},{}",
-                x,
-                arrow_tip_y,
-                x - arrow_size / 2.0,
-                arrow_tip_y - arrow_size,
-                x + arrow_size / 2.0,
-                arrow_tip_y - arrow_size
+                    .set("class", "column-line")
+                    .set("x1", *x)
+                    .set("y1", panel.column_line_top)
+                    .set("x2", *x)
+                    .set("y2", panel.column_line_bottom),
             );
+        }
+    }
 
-            main_group = main_group.add(
-                Path::new()
-                    .set("class", "arrow-head")
-                    .set("d", format!("M {} Z", arrow_points)),
-            );
-
-            // Draw item label - position based on column
-            let label_y = (y + end_y) / 2.0; // Middle of the arrow
-
-            let mut label_text = item.id.clone();
-            let points_change = item.points_change();
-            if points_change != 0.0 {
-                label_text.push_str(&format!(
-                    ": {}{}",
-                    if points_change > 0.0 { "+" } else { "" },
-                    format_points(points_change)
-                ));
-            }
-
-            // Check if this is the last column
-            let is_last_column = from_idx == chart.columns.len() - 1;
-
-            if is_last_column {
-                // For last column, put label on the left
-                let label_x = x - vertical_label_offset;
-
-                main_group = main_group.add(
-                    Text::new(label_text)
-                        .set("class", "item-label")
-                        .set("x", label_x)
-                        .set("y", label_y)
-                        .set("style", "text-anchor: end") // End anchor so text ends at x position
-                        .set("dominant-baseline", "middle"),
-                );
-            } else {
-                // For other columns, put label on the right
-                let label_x = x + vertical_label_offset;
-
-                main_group = main_group.add(
-                    Text::new(label_text)
-                        .set("class", "item-label")
-                        .set("x", label_x)
-                        .set("y", label_y)
-                        .set("style", "text-anchor: start") // Use inline style to override CSS class
-                        .set("dominant-baseline", "middle"),
-                );
-            }
-        } else {
-            // Different columns - draw horizontal arrow
-            // Draw circle at start
+    // Work items
+    for item_layout in layout.panels.iter().flat_map(|panel| &panel.items) {
+        for circle in &item_layout.circles {
             main_group = main_group.add(
                 Circle::new()
-                    .set("class", "item-circle")
-                    .set("cx", from_x)
-                    .set("cy", y)
-                    .set("r", circle_radius),
+                    .set(
+                        "class",
+                        format!(
+                            "item-circle {}",
+                            points_change_type(circle.points_change).class_name()
+                        ),
+                    )
+                    .set("cx", circle.x)
+                    .set("cy", circle.y)
+                    .set("r", CIRCLE_RADIUS),
             );
-
-            // Add from points text in start circle
             main_group = main_group.add(
-                Text::new(&format_points(item.from_points))
+                Text::new(format_points(circle.points))
                     .set("class", "circle-text")
-                    .set("x", from_x)
-                    .set("y", y)
+                    .set("x", circle.x)
+                    .set("y", circle.y)
                     .set("text-anchor", "middle")
                     .set("dominant-baseline", "middle"),
             );
+        }
 
-            // Draw circle at end
-            main_group = main_group.add(
-                Circle::new()
-                    .set("class", "item-circle")
-                    .set("cx", to_x)
-                    .set("cy", y)
-                    .set("r", circle_radius),
-            );
-
-            // Add to points text in end circle
+        for arrow in &item_layout.arrows {
+            let movement_class = hop_change_type(arrow.from_column, arrow.to_column).class_name();
             main_group = main_group.add(
-                Text::new(&format_points(item.to_points))
-                    .set("class", "circle-text")
-                    .set("x", to_x)
-                    .set("y", y)
-                    .set("text-anchor", "middle")
-                    .set("dominant-baseline", "middle"),
+                Path::new()
+                    .set("class", format!("item-arrow {}", movement_class))
+                    .set("d", path_data(&arrow.line)),
             );
-
-            // Draw horizontal arrow line
-            let arrow_start_x = if from_idx < to_idx {
-                from_x + circle_radius
-            } else {
-                from_x - circle_radius
-            };
-            let arrow_end_x = if from_idx < to_idx {
-                to_x - circle_radius - arrow_size
-            } else {
-                to_x + circle_radius + arrow_size
-            };
-
             main_group = main_group.add(
-                Line::new()
-                    .set("class", "item-arrow")
-                    .set("x1", arrow_start_x)
-                    .set("y1", y)
-                    .set("x2", arrow_end_x)
-                    .set("y2", y),
+                Path::new()
+                    .set("class", format!("arrow-head {}", movement_class))
+                    .set("d", format!("{} Z", path_data(&arrow.head))),
             );
+        }
 
-            // Draw horizontal arrow head pointing to circle edge
-            let arrow_points = if from_idx < to_idx {
-                // Right-pointing arrow
-                let arrow_tip_x = to_x - circle_radius;
-                format!(
-                    "{},{} {},{} {},{}",
-                    arrow_tip_x,
-                    y,
-                    arrow_tip_x - arrow_size,
-                    y - arrow_size / 2.0,
-                    arrow_tip_x - arrow_size,
-                    y + arrow_size / 2.0
-                )
-            } else {
-                // Left-pointing arrow
-                let arrow_tip_x = to_x + circle_radius;
-                format!(
-                    "{},{} {},{} {},{}",
-                    arrow_tip_x,
-                    y,
-                    arrow_tip_x + arrow_size,
-                    y - arrow_size / 2.0,
-                    arrow_tip_x + arrow_size,
-                    y + arrow_size / 2.0
-                )
+        if let Some(ref label) = item_layout.label {
+            let mut text = Text::new(item_label(item_layout.item))
+                .set("class", "item-label")
+                .set("x", label.x)
+                .set("y", label.y);
+            text = match label.anchor {
+                LabelAnchor::Above => text.set("dominant-baseline", "text-after-edge"),
+                // Use inline style to override CSS class
+                LabelAnchor::Start => text
+                    .set("style", "text-anchor: start")
+                    .set("dominant-baseline", "middle"),
+                LabelAnchor::End => text
+                    .set("style", "text-anchor: end")
+                    .set("dominant-baseline", "middle"),
             };
+            main_group = main_group.add(text);
+        }
+    }
 
-            main_group = main_group.add(
-                Path::new()
-                    .set("class", "arrow-head")
-                    .set("d", format!("M {} Z", arrow_points)),
-            );
+    if let Some(ref summary_layout) = layout.summary {
+        let summary = summarise_work_item_movement(chart);
+        main_group = main_group.add(
+            Line::new()
+                .set("class", "summary-line")
+                .set("x1", MARGIN)
+                .set("y1", summary_layout.top)
+                .set("x2", width as f64 - MARGIN)
+                .set("y2", summary_layout.top),
+        );
 
-            // Draw item label above the line
-            let label_x = (from_x + to_x) / 2.0;
-            let label_y = y - 5.0; // Just 5 pixels above the line
-
-            let mut label_text = item.id.clone();
-            let points_change = item.points_change();
-            if points_change != 0.0 {
-                label_text.push_str(&format!(
-                    ": {}{}",
-                    if points_change > 0.0 { "+" } else { "" },
-                    format_points(points_change)
-                ));
+        for (column, x) in summary.columns.iter().zip(&summary_layout.column_positions) {
+            let lines = [
+                (
+                    format!("In: {}", format_points(column.entered)),
+                    "summary-text",
+                ),
+                (
+                    format!("Out: {}", format_points(column.left)),
+                    "summary-text",
+                ),
+                (
+                    format!("Net: {}", format_points_change(column.net())),
+                    "summary-text summary-net",
+                ),
+            ];
+            for ((text, class), y) in lines.into_iter().zip(summary_layout.column_line_ys) {
+                main_group =
+                    main_group.add(Text::new(text).set("class", class).set("x", *x).set("y", y));
             }
-
-            main_group = main_group.add(
-                Text::new(label_text)
-                    .set("class", "item-label")
-                    .set("x", label_x)
-                    .set("y", label_y)
-                    .set("dominant-baseline", "text-after-edge"),
-            );
         }
 
-        // Update current_y for next item
-        if from_idx == to_idx {
-            // Vertical arrow takes more space
-            current_y += vertical_arrow_spacing + item_height;
-        } else {
-            // Regular horizontal arrow
-            current_y += item_height;
-        }
+        main_group = main_group.add(
+            Text::new(format!(
+                "Scope added: {}, removed: {}, net change: {}",
+                format_points(summary.scope_added),
+                format_points(summary.scope_removed),
+                format_points_change(summary.net_scope_change())
+            ))
+            .set("class", "summary-text summary-net")
+            .set("x", width as f64 / 2.0)
+            .set("y", summary_layout.scope_y),
+        );
+    }
+
+    if legend_layout.rows > 0 {
+        let legend_colors: Vec<String> = legend_entries
+            .iter()
+            .map(|change_type| {
+                let (key, default) = change_type.theme_variable();
+                get_theme_variable(chart, key, default).to_string()
+            })
+            .collect();
+        main_group = main_group.add(render_legend(
+            &legend_labels,
+            &legend_colors,
+            (width as f64 - legend_layout.width) / 2.0,
+            layout.legend_y,
+            &legend_layout,
+            &legend_config,
+        ));
     }
 
     document = document.add(main_group);
 
     (document, width, height)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::work_item_movement::layout::{DEFAULT_SPACING, MIN_SPACING};
+    use crate::work_item_movement::parse_work_item_movement;
+
+    fn chart(input: &str) -> WorkItemMovement {
+        parse_work_item_movement(input, None).unwrap().1
+    }
+
+    #[test]
+    fn test_hop_change_type() {
+        assert_eq!(hop_change_type(0, 2), ChangeType::New);
+        assert_eq!(hop_change_type(1, 2), ChangeType::Forward);
+        assert_eq!(hop_change_type(2, 1), ChangeType::Regression);
+        assert_eq!(hop_change_type(1, 0), ChangeType::Regression);
+        assert_eq!(hop_change_type(1, 1), ChangeType::Unchanged);
+    }
+
+    #[test]
+    fn test_points_change_type() {
+        assert_eq!(points_change_type(2.0), ChangeType::ScopeIncrease);
+        assert_eq!(points_change_type(-0.5), ChangeType::ScopeDecrease);
+        assert_eq!(points_change_type(0.0), ChangeType::Unchanged);
+    }
+
+    #[test]
+    fn test_used_change_types() {
+        // The backward hop is a regression even though the item ends up further along
+        let regressed = chart(
+            "work-item-movement\n  columns [To Do, In Progress, Done]\n  PJ-1 To Do: 3 -> Done: 3 -> In Progress: 5\n",
+        );
+        assert_eq!(
+            used_change_types(&regressed),
+            vec![
+                ChangeType::New,
+                ChangeType::Regression,
+                ChangeType::ScopeIncrease,
+                ChangeType::Unchanged
+            ]
+        );
+
+        let moved_forward = chart(
+            "work-item-movement\n  columns [To Do, In Progress, Done]\n  PJ-1 In Progress: 3 -> Done: 2\n  PJ-2 Done: 1 -> Done: 1\n",
+        );
+        assert_eq!(
+            used_change_types(&moved_forward),
+            vec![
+                ChangeType::Forward,
+                ChangeType::ScopeDecrease,
+                ChangeType::Unchanged
+            ]
+        );
+    }
+
+    /// The y position of every item circle in a rendered chart
+    fn item_circle_ys(svg: &str) -> Vec<f64> {
+        svg.split("<circle")
+            .skip(1)
+            .map(|tag| &tag[..tag.find('>').unwrap()])
+            .filter(|tag| tag.contains("item-circle"))
+            .map(|tag| {
+                let start = tag.find(" cy=\"").unwrap() + 5;
+                tag[start..].split('"').next().unwrap().parse().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tall_chart_compacts_then_splits_into_panels() {
+        let mut input = String::from("work-item-movement\n  columns [To Do, Done]\n");
+        for i in 0..10 {
+            input.push_str(&format!("  PJ-{} To Do: 1 -> Done: 1\n", i));
+        }
+        let chart = chart(&input);
+        let render =
+            |max_height| render_work_item_movement_svg(&chart, 800, max_height, "No Such Font");
+
+        let (_, _, default_height) = render(2000);
+
+        // Only the minimum spacing in the nine gaps between the ten items fits
+        let max_height =
+            default_height - 9 * (DEFAULT_SPACING.item_height - MIN_SPACING.item_height) as u32 + 1;
+        let (document, _, height) = render(max_height);
+        assert!(height <= max_height);
+        let mut ys = item_circle_ys(&document.to_string());
+        ys.dedup();
+        assert_eq!(ys.len(), 10);
+        assert!(ys
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] == MIN_SPACING.item_height));
+
+        // Any shorter and the items are split across two panels, each with its own column labels
+        let max_height = max_height - 100;
+        let (document, _, height) = render(max_height);
+        assert!(height <= max_height);
+        assert_eq!(
+            document
+                .to_string()
+                .matches("class=\"column-label\"")
+                .count(),
+            4
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use super::WorkItemMovement;
+
+/// The points that moved in and out of a column
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnSummary {
+    pub entered: f64,
+    pub left: f64,
+}
+
+impl ColumnSummary {
+    pub fn net(&self) -> f64 {
+        self.entered - self.left
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MovementSummary {
+    /// One summary per column in the same order as the chart's columns
+    pub columns: Vec<ColumnSummary>,
+    /// The sum of the increases in points across all items
+    pub scope_added: f64,
+    /// The sum of the decreases in points across all items as a positive number
+    pub scope_removed: f64,
+}
+
+impl MovementSummary {
+    pub fn net_scope_change(&self) -> f64 {
+        self.scope_added - self.scope_removed
+    }
+}
+
+/// Total up the movement of points between columns. Every move between columns counts the
+/// points leaving the previous column and entering the next one, so an item passing through a
+/// column counts both. When an item's points change while it stays in the same column the
+/// difference counts as entering or leaving that column, which keeps each column's net change
+/// equal to its final points minus its starting points.
+pub fn summarise_work_item_movement(chart: &WorkItemMovement) -> MovementSummary {
+    let mut summary = MovementSummary {
+        columns: vec![ColumnSummary::default(); chart.columns.len()],
+        ..Default::default()
+    };
+
+    for item in &chart.items {
+        for transition in item.states.windows(2) {
+            let (from, to) = (&transition[0], &transition[1]);
+            let from_column = chart.column_index(&from.name);
+            let to_column = chart.column_index(&to.name);
+
+            if from_column == to_column {
+                if let Some(column) = from_column {
+                    let change = to.points - from.points;
+                    if change > 0.0 {
+                        summary.columns[column].entered += change;
+                    } else {
+                        summary.columns[column].left -= change;
+                    }
+                }
+                continue;
+            }
+
+            if let Some(column) = from_column {
+                summary.columns[column].left += from.points;
+            }
+            if let Some(column) = to_column {
+                summary.columns[column].entered += to.points;
+            }
+        }
+
+        let points_change = item.points_change();
+        if points_change > 0.0 {
+            summary.scope_added += points_change;
+        } else {
+            summary.scope_removed -= points_change;
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::work_item_movement::parse_work_item_movement;
+
+    #[test]
+    fn test_summarise_work_item_movement() {
+        let input = r#"work-item-movement showSummary
+  columns [To Do, In Progress, Done]
+  PJ-1 To Do: 1 -> In Progress: 2 -> Done: 3
+  PJ-2 in progress: 5 -> In Progress: 3
+  PJ-3 Done: 2 -> To Do: 2
+"#;
+
+        let (_, chart) = parse_work_item_movement(input, None).unwrap();
+        assert!(chart.show_summary);
+
+        let summary = summarise_work_item_movement(&chart);
+        assert_eq!(
+            summary.columns,
+            vec![
+                ColumnSummary {
+                    entered: 2.0,
+                    left: 1.0
+                },
+                ColumnSummary {
+                    entered: 2.0,
+                    left: 4.0
+                },
+                ColumnSummary {
+                    entered: 3.0,
+                    left: 2.0
+                },
+            ]
+        );
+        assert_eq!(summary.columns[1].net(), -2.0);
+        assert_eq!(summary.scope_added, 2.0);
+        assert_eq!(summary.scope_removed, 2.0);
+        assert_eq!(summary.net_scope_change(), 0.0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use super::{GroupBy, SortBy, WorkItem, WorkItemMovement};
+use crate::common::writer::quote;
+
+/// Column and state names are only quoted when they contain the separators around them or
+/// would lose whitespace
+fn format_name(name: &str) -> String {
+    if name.trim().is_empty()
+        || name.trim() != name
+        || name.starts_with(['"', '\''])
+        || name.contains([',', ']', ':'])
+        || name.contains("->")
+    {
+        quote(name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Ids are only quoted when they can't be parsed as a token
+fn format_id(id: &str) -> String {
+    if id.is_empty() || id.starts_with(['"', '\'']) || id.contains(char::is_whitespace) {
+        quote(id)
+    } else {
+        id.to_string()
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SortBy::Column => "column",
+            SortBy::Points => "points",
+            SortBy::Id => "id",
+        })
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            GroupBy::Prefix => "prefix",
+            GroupBy::Column => "column",
+        })
+    }
+}
+
+impl WorkItemMovement {
+    /// The chart in mermaid syntax, preceded by the `%%{init}%%` line of any config
+    pub fn to_mermaid(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Write the chart in the syntax accepted by `parse_work_item_movement`. Items without a section
+/// are written before the first `section` line since every item after it is in a section.
+impl fmt::Display for WorkItemMovement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(config) = &self.config {
+            writeln!(f, "{}", config)?;
+        }
+
+        write!(f, "work-item-movement")?;
+        if self.show_summary {
+            write!(f, " showSummary")?;
+        }
+        writeln!(f)?;
+
+        if let Some(title) = &self.title {
+            writeln!(f, "  title {}", quote(title))?;
+        }
+        let columns: Vec<String> = self.columns.iter().map(|name| format_name(name)).collect();
+        writeln!(f, "  columns [{}]", columns.join(", "))?;
+        if let Some(sort_by) = self.sort_by {
+            writeln!(f, "  sort by {}", sort_by)?;
+        }
+        if let Some(group_by) = self.group_by {
+            writeln!(f, "  group by {}", group_by)?;
+        }
+
+        let (unsectioned, sectioned): (Vec<&WorkItem>, Vec<&WorkItem>) =
+            self.items.iter().partition(|item| item.section.is_none());
+        let mut section = None;
+        for item in unsectioned.into_iter().chain(sectioned) {
+            if item.section.is_some() && item.section != section {
+                section = item.section.clone();
+                writeln!(
+                    f,
+                    "  section {}",
+                    quote(section.as_deref().unwrap_or_default())
+                )?;
+            }
+
+            write!(f, "  {}", format_id(&item.id))?;
+            if let Some(title) = &item.title {
+                write!(f, " {}", quote(title))?;
+            }
+            let states: Vec<String> = item
+                .states
+                .iter()
+                .map(|state| format!("{}: {}", format_name(&state.name), state.points))
+                .collect();
+            writeln!(f, " {}", states.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::parser::parse_config_and_detect_type;
+    use crate::common::writer::strategies::{chart_config, quotable_text};
+    use crate::work_item_movement::{
+        parse_work_item_movement, GroupBy, SortBy, WorkItem, WorkItemMovement, WorkItemState,
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_write_work_item_movement() {
+        let input = r#"%%{init: {'theme': 'base', 'themeVariables': {'workItemMovement': {'forwardColor': '#2ca02c'}}}}%%
+work-item-movement showSummary
+  title "Sprint 12's changes"
+  columns [Not Existing, To Do, Done, "QA: Ready", "Done, #quot;really#quot;"]
+  sort by id
+  group by prefix
+  PJ-1 To Do: 1 -> Done: 1.5
+  section "Team A"
+  "550e8400 e29b" "Say #quot;hello#quot;" Not Existing: 0 -> To Do: 2 -> Done: 3
+  #12 "Fix the build" To Do: 5 -> To Do: 8
+  PJ-2 "QA: Ready": 1 -> "Done, #quot;really#quot;": 2
+"#;
+
+        let (_, (config, _, content)) = parse_config_and_detect_type(input).unwrap();
+        let (_, chart) = parse_work_item_movement(content, config).unwrap();
+        let written = chart.to_mermaid();
+        assert_eq!(written, input);
+    }
+
+    /// Column names, including ones that contain the separators of the columns list or states
+    fn column() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[A-Z][a-zA-Z0-9 #-]{0,10}[a-zA-Z0-9#-]|[A-Z]",
+            quotable_text().prop_filter("empty", |name| !name.is_empty()),
+        ]
+    }
+
+    fn chart() -> impl Strategy<Value = WorkItemMovement> {
+        let items = (
+            prop_oneof!["[A-Z]{1,4}-[0-9]{1,4}", quotable_text()],
+            prop::option::of(quotable_text()),
+            prop::option::of(quotable_text()),
+            prop::collection::vec((any::<prop::sample::Index>(), 0f64..1e6), 2..5),
+        );
+        (
+            prop::option::of(chart_config()),
+            any::<bool>(),
+            prop::option::of(quotable_text()),
+            prop::collection::vec(column(), 1..5),
+            prop::collection::vec(items, 0..6),
+            prop::option::of(prop_oneof![
+                Just(SortBy::Column),
+                Just(SortBy::Points),
+                Just(SortBy::Id)
+            ]),
+            prop::option::of(prop_oneof![Just(GroupBy::Prefix), Just(GroupBy::Column)]),
+        )
+            .prop_map(
+                |(config, show_summary, title, columns, items, sort_by, group_by)| {
+                    let items = items
+                        .into_iter()
+                        .map(|(id, title, section, states)| WorkItem {
+                            id,
+                            title,
+                            section,
+                            states: states
+                                .into_iter()
+                                .map(|(index, points)| WorkItemState {
+                                    name: index.get(&columns).clone(),
+                                    points,
+                                })
+                                .collect(),
+                        })
+                        .collect();
+                    WorkItemMovement {
+                        config,
+                        title,
+                        show_summary,
+                        columns,
+                        items,
+                        sort_by,
+                        group_by,
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_work_item_movement_round_trip(chart in chart()) {
+            let written = chart.to_mermaid();
+            let (_, (config, _, content)) = parse_config_and_detect_type(&written).unwrap();
+            let (remaining, parsed) = parse_work_item_movement(content, config).unwrap();
+            prop_assert_eq!(remaining, "");
+
+            // Items without a section are read back before the items in sections
+            let (mut items, sectioned): (Vec<WorkItem>, Vec<WorkItem>) =
+                chart.items.into_iter().partition(|item| item.section.is_none());
+            items.extend(sectioned);
+            prop_assert_eq!(parsed, WorkItemMovement { items, ..chart });
+        }
+    }
+}
@@ -1,24 +1,33 @@
 use nom::{
-    bytes::complete::tag,
+    branch::alt,
+    bytes::complete::{tag, take_till1},
     character::complete::{char, multispace0, space0},
-    combinator::opt,
+    combinator::{map, opt},
     multi::separated_list0,
-    sequence::{preceded, tuple},
+    sequence::{preceded, terminated, tuple},
     IResult,
 };
 
-use super::{Series, SeriesType, XAxis, XYChart, YAxis};
+use super::{
+    Annotation, AnnotationKind, AxisSide, Curve, Series, SeriesType, XAxis, XYChart, YAxis,
+};
 use crate::common::{
     number,
-    string_parser::{parse_labels_list, quoted_string, take_until_any},
+    string_parser::{any_quoted_string, parse_attributes, parse_labels_list, take_until_any},
     ChartConfig,
 };
 
+/// The values of the `points` attribute
+const POINT_SHAPES: [&str; 3] = ["none", "square", "diamond"];
+
+/// The values of the `stroke` attribute
+const STROKE_STYLES: [&str; 2] = ["solid", "dashed"];
+
 fn xy_header(input: &str) -> IResult<&str, Option<String>> {
     let (input, _) = tag("xychart-beta")(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, title) = opt(preceded(tag("title "), quoted_string))(input)?;
-    Ok((input, title.map(|s| s.to_string())))
+    let (input, title) = opt(preceded(tag("title "), any_quoted_string))(input)?;
+    Ok((input, title))
 }
 
 fn x_axis_line(input: &str) -> IResult<&str, XAxis> {
@@ -39,10 +48,22 @@ fn legend_line(input: &str) -> IResult<&str, Vec<String>> {
     Ok((input, labels))
 }
 
-fn y_axis_line(input: &str) -> IResult<&str, YAxis> {
+/// Parse an optional `left` or `right` keyword followed by whitespace
+fn axis_side(input: &str) -> IResult<&str, Option<AxisSide>> {
+    opt(terminated(
+        alt((
+            map(tag("left"), |_| AxisSide::Left),
+            map(tag("right"), |_| AxisSide::Right),
+        )),
+        space0,
+    ))(input)
+}
+
+fn y_axis_line(input: &str) -> IResult<&str, (AxisSide, YAxis)> {
     let (input, _) = tag("y-axis")(input)?;
     let (input, _) = space0(input)?;
-    let (input, title) = quoted_string(input)?;
+    let (input, side) = axis_side(input)?;
+    let (input, title) = any_quoted_string(input)?;
     let (input, _) = space0(input)?;
     let (input, min) = number(input)?;
     let (input, _) = space0(input)?;
@@ -50,30 +71,164 @@ fn y_axis_line(input: &str) -> IResult<&str, YAxis> {
     let (input, _) = space0(input)?;
     let (input, max) = number(input)?;
 
-    Ok((
-        input,
-        YAxis {
-            title: title.to_string(),
-            min,
-            max,
-        },
-    ))
+    Ok((input, (side.unwrap_or_default(), YAxis { title, min, max })))
+}
+
+/// Parse the left y-axis and an optional right y-axis, which may be given in either order
+fn y_axes(input: &str) -> IResult<&str, (YAxis, Option<YAxis>)> {
+    let (input, first) = y_axis_line(input)?;
+    let (after_second, second) = opt(preceded(multispace0, y_axis_line))(input)?;
+
+    match (first, second) {
+        ((AxisSide::Left, left), None) => Ok((input, (left, None))),
+        ((AxisSide::Left, left), Some((AxisSide::Right, right)))
+        | ((AxisSide::Right, right), Some((AxisSide::Left, left))) => {
+            Ok((after_second, (left, Some(right))))
+        }
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
+/// Parse a data value, `null` or `_` is a gap in the series
+fn data_value(input: &str) -> IResult<&str, Option<f64>> {
+    alt((
+        map(number, Some),
+        map(alt((tag("null"), tag("_"))), |_| None),
+    ))(input)
 }
 
 fn series_line(input: &str) -> IResult<&str, Series> {
     let (input, series_type_str) = take_until_any(&[' ', '\t'])(input)?;
     let (input, _) = space0(input)?;
+    let (input, axis) = axis_side(input)?;
+    let (input, name) = opt(terminated(any_quoted_string, space0))(input)?;
+    let attributes_start = input;
+    let (input, attributes) = opt(terminated(parse_attributes, space0))(input)?;
     let (input, _) = char('[')(input)?;
-    let (input, data) = separated_list0(tuple((space0, char(','), space0)), number)(input)?;
+    let (input, data) = separated_list0(tuple((space0, char(','), space0)), data_value)(input)?;
     let (input, _) = char(']')(input)?;
 
     let series_type = match series_type_str.trim() {
         "bar" => SeriesType::Bar,
         "line" => SeriesType::Line,
+        "area" => SeriesType::Area,
+        "stacked-area" => SeriesType::StackedArea,
         _ => SeriesType::Bar, // Default to bar
     };
 
-    Ok((input, Series { series_type, data }))
+    let mut series = Series {
+        series_type,
+        axis: axis.unwrap_or_default(),
+        name,
+        color: None,
+        points: None,
+        stroke: None,
+        curve: None,
+        opacity: None,
+        data,
+    };
+
+    for (key, value) in attributes.unwrap_or_default() {
+        match key.as_str() {
+            "color" => series.color = Some(value),
+            "points" if POINT_SHAPES.contains(&value.as_str()) => series.points = Some(value),
+            "stroke" if STROKE_STYLES.contains(&value.as_str()) => series.stroke = Some(value),
+            "curve" => {
+                series.curve = Some(match value.as_str() {
+                    "linear" => Curve::Linear,
+                    "step" => Curve::Step,
+                    "monotone" => Curve::Monotone,
+                    _ => return Err(invalid_attribute(attributes_start)),
+                })
+            }
+            "opacity" => {
+                series.opacity = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid_attribute(attributes_start))?,
+                )
+            }
+            _ => return Err(invalid_attribute(attributes_start)),
+        }
+    }
+
+    Ok((input, series))
+}
+
+/// Error for an invalid attribute, this fails rather than errors so the statement isn't
+/// silently skipped along with the rest of the chart
+fn invalid_attribute(input: &str) -> nom::Err<nom::error::Error<&str>> {
+    nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Verify))
+}
+
+/// Parse `hline`, `vline` and `band` statements which can be followed by an optional label
+/// and attributes, e.g. `hline 120 "Scope" {color: #aaaaaa, stroke: dashed}`
+fn annotation_line(input: &str) -> IResult<&str, Annotation> {
+    let (input, keyword) = alt((tag("hline"), tag("vline"), tag("band")))(input)?;
+    let (input, _) = space0(input)?;
+    let (input, axis) = axis_side(input)?;
+
+    let (input, kind) = match keyword {
+        "hline" => map(number, |value| AnnotationKind::HorizontalLine { value })(input)?,
+        "vline" => map(
+            alt((
+                any_quoted_string,
+                map(take_till1(|c: char| c.is_whitespace()), str::to_string),
+            )),
+            |category| AnnotationKind::VerticalLine { category },
+        )(input)?,
+        _ => map(
+            tuple((number, space0, tag("-->"), space0, number)),
+            |(from, _, _, _, to)| AnnotationKind::Band { from, to },
+        )(input)?,
+    };
+
+    let (input, _) = space0(input)?;
+    let (input, label) = opt(terminated(any_quoted_string, space0))(input)?;
+    let attributes_start = input;
+    let (input, attributes) = opt(parse_attributes)(input)?;
+
+    let mut annotation = Annotation {
+        kind,
+        axis: axis.unwrap_or_default(),
+        label,
+        color: None,
+        stroke: None,
+        opacity: None,
+    };
+
+    for (key, value) in attributes.unwrap_or_default() {
+        match key.as_str() {
+            "color" => annotation.color = Some(value),
+            "stroke" if STROKE_STYLES.contains(&value.as_str()) => annotation.stroke = Some(value),
+            "opacity" => {
+                annotation.opacity = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid_attribute(attributes_start))?,
+                )
+            }
+            _ => return Err(invalid_attribute(attributes_start)),
+        }
+    }
+
+    Ok((input, annotation))
+}
+
+/// The statements that can follow the axes
+enum Statement {
+    Series(Series),
+    Annotation(Annotation),
+}
+
+fn statement(input: &str) -> IResult<&str, Statement> {
+    alt((
+        map(annotation_line, Statement::Annotation),
+        map(series_line, Statement::Series),
+    ))(input)
 }
 
 pub fn parse_xychart_content(input: &str, config: Option<ChartConfig>) -> IResult<&str, XYChart> {
@@ -83,11 +238,20 @@ pub fn parse_xychart_content(input: &str, config: Option<ChartConfig>) -> IResul
     let (input, _) = multispace0(input)?;
     let (input, x_axis) = x_axis_line(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, y_axis) = y_axis_line(input)?;
+    let (input, (y_axis, y_axis_right)) = y_axes(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, series) = separated_list0(multispace0, series_line)(input)?;
+    let (input, statements) = separated_list0(multispace0, statement)(input)?;
     let (input, _) = multispace0(input)?;
 
+    let mut series = Vec::new();
+    let mut annotations = Vec::new();
+    for statement in statements {
+        match statement {
+            Statement::Series(s) => series.push(s),
+            Statement::Annotation(a) => annotations.push(a),
+        }
+    }
+
     Ok((
         input,
         XYChart {
@@ -96,7 +260,9 @@ pub fn parse_xychart_content(input: &str, config: Option<ChartConfig>) -> IResul
             legend,
             x_axis,
             y_axis,
+            y_axis_right,
             series,
+            annotations,
         },
     ))
 }
@@ -0,0 +1,226 @@
+use super::{AxisSide, Series, SeriesType, XAxis, XYChart, YAxis};
+use crate::common::data::{DataError, DataTable};
+
+/// The columns of a data table that an xy chart is built from
+#[derive(Debug, Clone, PartialEq)]
+pub struct XYDataSpec {
+    /// The column of x-axis labels, defaults to the first column
+    pub x_column: Option<String>,
+    /// The columns plotted as series, defaults to every column other than the x column
+    pub series: Vec<String>,
+    pub series_type: SeriesType,
+    pub title: Option<String>,
+    /// Defaults to the name of the series when there is only one
+    pub y_axis_title: Option<String>,
+    /// Defaults to zero or the smallest value when it is negative
+    pub y_min: Option<f64>,
+    /// Defaults to a round number at or above the largest value
+    pub y_max: Option<f64>,
+}
+
+impl Default for XYDataSpec {
+    fn default() -> Self {
+        Self {
+            x_column: None,
+            series: Vec::new(),
+            series_type: SeriesType::Bar,
+            title: None,
+            y_axis_title: None,
+            y_min: None,
+            y_max: None,
+        }
+    }
+}
+
+/// The smallest of 1, 2, 2.5 or 5 times a power of ten that is at least the value
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|ceiling| *ceiling >= value)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Build an xy chart with an x-axis category for each row of the table and a series for each
+/// series column, empty values are gaps in the series
+pub fn xychart_from_data(table: &DataTable, spec: &XYDataSpec) -> Result<XYChart, DataError> {
+    let x_column = match &spec.x_column {
+        Some(column) => column.clone(),
+        None => table.columns.first().cloned().ok_or_else(|| DataError {
+            message: "XY chart data needs an x column".to_string(),
+        })?,
+    };
+    let labels = table.text_column(&x_column)?;
+
+    let series_columns: Vec<String> = if spec.series.is_empty() {
+        table
+            .columns
+            .iter()
+            .filter(|column| **column != x_column)
+            .cloned()
+            .collect()
+    } else {
+        spec.series.clone()
+    };
+    if series_columns.is_empty() {
+        return Err(DataError {
+            message: "XY chart data needs at least one series column".to_string(),
+        });
+    }
+
+    let series = series_columns
+        .iter()
+        .map(|column| {
+            Ok(Series {
+                series_type: spec.series_type.clone(),
+                axis: AxisSide::Left,
+                name: Some(column.clone()),
+                color: None,
+                points: None,
+                stroke: None,
+                curve: None,
+                opacity: None,
+                data: table.number_column(column)?,
+            })
+        })
+        .collect::<Result<Vec<_>, DataError>>()?;
+
+    // Stacked areas are drawn on top of each other so the axis has to fit their totals
+    let values: Vec<f64> = if spec.series_type == SeriesType::StackedArea {
+        (0..labels.len())
+            .map(|index| series.iter().filter_map(|series| series.data[index]).sum())
+            .collect()
+    } else {
+        series
+            .iter()
+            .flat_map(|series| series.data.iter().flatten().copied())
+            .collect()
+    };
+    let smallest = values.iter().copied().fold(0.0, f64::min);
+    let largest = values.iter().copied().fold(0.0, f64::max);
+    let min = spec.y_min.unwrap_or(if smallest < 0.0 {
+        -nice_ceiling(-smallest)
+    } else {
+        0.0
+    });
+    let max = spec.y_max.unwrap_or_else(|| nice_ceiling(largest));
+
+    Ok(XYChart {
+        config: None,
+        title: spec.title.clone(),
+        legend: None,
+        x_axis: XAxis { labels },
+        y_axis: YAxis {
+            title: spec.y_axis_title.clone().unwrap_or_else(|| {
+                if series_columns.len() == 1 {
+                    series_columns[0].clone()
+                } else {
+                    String::new()
+                }
+            }),
+            min,
+            // The axis needs a range even when every value is zero or there are no rows
+            max: if max > min { max } else { min + 1.0 },
+        },
+        y_axis_right: None,
+        series,
+        annotations: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xychart_from_data() {
+        let table =
+            DataTable::from_csv("week,done,review,blocked\nW1,3,2,1\nW2,5,,-1\nW3,12,1,0\n")
+                .unwrap();
+
+        let xychart = xychart_from_data(
+            &table,
+            &XYDataSpec {
+                x_column: Some("week".to_string()),
+                series: vec!["done".to_string(), "review".to_string()],
+                series_type: SeriesType::Line,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(xychart.x_axis.labels, vec!["W1", "W2", "W3"]);
+        assert_eq!(xychart.series.len(), 2);
+        assert_eq!(xychart.series[0].name.as_deref(), Some("done"));
+        assert_eq!(xychart.series[0].series_type, SeriesType::Line);
+        assert_eq!(xychart.series[1].data, vec![Some(2.0), None, Some(1.0)]);
+        assert_eq!(xychart.y_axis.title, "");
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (0.0, 20.0));
+
+        let xychart = xychart_from_data(&table, &XYDataSpec::default()).unwrap();
+        assert_eq!(xychart.series.len(), 3);
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (-1.0, 20.0));
+
+        let xychart = xychart_from_data(
+            &table,
+            &XYDataSpec {
+                series: vec!["done".to_string(), "review".to_string()],
+                series_type: SeriesType::StackedArea,
+                y_min: Some(0.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (0.0, 20.0));
+
+        let xychart = xychart_from_data(
+            &table,
+            &XYDataSpec {
+                series: vec!["done".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(xychart.y_axis.title, "done");
+
+        let zeros = DataTable::from_csv("week,done\nW1,0\nW2,0\n").unwrap();
+        let xychart = xychart_from_data(&zeros, &XYDataSpec::default()).unwrap();
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (0.0, 1.0));
+
+        let empty = DataTable::from_csv("week,done\n").unwrap();
+        let xychart = xychart_from_data(&empty, &XYDataSpec::default()).unwrap();
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (0.0, 1.0));
+
+        let xychart = xychart_from_data(
+            &zeros,
+            &XYDataSpec {
+                y_min: Some(5.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((xychart.y_axis.min, xychart.y_axis.max), (5.0, 6.0));
+
+        assert!(xychart_from_data(
+            &table,
+            &XYDataSpec {
+                series: vec!["missing".to_string()],
+                ..Default::default()
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_nice_ceiling() {
+        assert_eq!(nice_ceiling(0.0), 0.0);
+        assert_eq!(nice_ceiling(3.0), 5.0);
+        assert_eq!(nice_ceiling(12.0), 20.0);
+        assert_eq!(nice_ceiling(21.0), 25.0);
+        assert_eq!(nice_ceiling(100.0), 100.0);
+        assert_eq!(nice_ceiling(0.3), 0.5);
+    }
+}
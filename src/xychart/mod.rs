@@ -1,21 +1,31 @@
 use crate::common::ChartConfig;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct XYChart {
     pub config: Option<ChartConfig>,
     pub title: Option<String>,
     pub legend: Option<Vec<String>>,
     pub x_axis: XAxis,
     pub y_axis: YAxis,
+    pub y_axis_right: Option<YAxis>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub series: Vec<Series>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub annotations: Vec<Annotation>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct XAxis {
     pub labels: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct YAxis {
     pub title: String,
     pub min: f64,
@@ -23,21 +33,97 @@ pub struct YAxis {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum SeriesType {
     Bar,
     Line,
+    /// A line with the area between it and the bottom of the chart filled
+    Area,
+    /// An area drawn on top of the previous stacked areas on the same axis
+    StackedArea,
+}
+
+/// The y-axis a series is plotted against
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AxisSide {
+    #[default]
+    Left,
+    Right,
+}
+
+/// How the points of a line or area are joined
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Curve {
+    #[default]
+    Linear,
+    /// Each value is held until the next point
+    Step,
+    /// A smooth curve that never overshoots the points
+    Monotone,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Series {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub series_type: SeriesType,
-    pub data: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub axis: AxisSide,
+    /// Name shown in the legend when no explicit `legend` is given
+    pub name: Option<String>,
+    /// These take precedence over the `xyChart` theme variable lists
+    pub color: Option<String>,
+    pub points: Option<String>,
+    pub stroke: Option<String>,
+    /// How points are joined, lines are straight by default
+    pub curve: Option<Curve>,
+    /// Fill opacity of area series
+    pub opacity: Option<f64>,
+    /// A `None` value is a gap, which breaks lines and areas and skips bars
+    pub data: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "camelCase"))]
+pub enum AnnotationKind {
+    /// A horizontal line at a value on the y-axis
+    HorizontalLine { value: f64 },
+    /// A vertical line through the middle of an x-axis category
+    VerticalLine { category: String },
+    /// A shaded range between two values on the y-axis
+    Band { from: f64, to: f64 },
+}
+
+/// A reference line or band drawn behind the series
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Annotation {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub kind: AnnotationKind,
+    /// The y-axis that horizontal lines and bands are positioned against
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub axis: AxisSide,
+    pub label: Option<String>,
+    pub color: Option<String>,
+    pub stroke: Option<String>,
+    pub opacity: Option<f64>,
 }
 
 pub mod content_parser;
+pub mod data;
 pub mod parser;
 pub mod renderer;
+pub mod writer;
 
 pub use content_parser::*;
+pub use data::*;
 pub use parser::*;
 pub use renderer::*;
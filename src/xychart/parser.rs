@@ -1,128 +1,19 @@
-use nom::{
-    bytes::complete::tag,
-    character::complete::{char, multispace0, space0},
-    combinator::opt,
-    multi::separated_list0,
-    sequence::{preceded, tuple},
-    IResult,
-};
-
-use super::{Series, SeriesType, XAxis, XYChart, YAxis};
-use crate::common::{
-    config_line, number,
-    string_parser::{parse_labels_list, quoted_string, take_until_any},
-};
-
-fn xy_header(input: &str) -> IResult<&str, Option<String>> {
-    let (input, _) = tag("xychart-beta")(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, title) = opt(preceded(tag("title "), quoted_string))(input)?;
-    Ok((input, title.map(|s| s.to_string())))
-}
-
-fn x_axis_line(input: &str) -> IResult<&str, XAxis> {
-    let (input, _) = tag("x-axis")(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = char('[')(input)?;
-    let (input, labels) = parse_labels_list(input)?;
-    let (input, _) = char(']')(input)?;
-
-    Ok((input, XAxis { labels }))
-}
-
-fn y_axis_line(input: &str) -> IResult<&str, YAxis> {
-    let (input, _) = tag("y-axis")(input)?;
-    let (input, _) = space0(input)?;
-    let (input, title) = quoted_string(input)?;
-    let (input, _) = space0(input)?;
-    let (input, min) = number(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = tag("-->")(input)?;
-    let (input, _) = space0(input)?;
-    let (input, max) = number(input)?;
-
-    Ok((
-        input,
-        YAxis {
-            title: title.to_string(),
-            min,
-            max,
-        },
-    ))
-}
+use nom::{character::complete::multispace0, combinator::opt, sequence::preceded, IResult};
 
-fn legend_line(input: &str) -> IResult<&str, Vec<String>> {
-    let (input, _) = char('[')(input)?;
-    let (input, labels) = parse_labels_list(input)?;
-    let (input, _) = char(']')(input)?;
-
-    Ok((input, labels))
-}
-
-fn series_line(input: &str) -> IResult<&str, Series> {
-    let (input, series_type_str) = take_until_any(&[' ', '\t'])(input)?;
-    let (input, _) = space0(input)?;
-    let (input, _) = char('[')(input)?;
-    let (input, data) = separated_list0(tuple((space0, char(','), space0)), number)(input)?;
-    let (input, _) = char(']')(input)?;
-
-    let series_type = match series_type_str.trim() {
-        "bar" => SeriesType::Bar,
-        "line" => SeriesType::Line,
-        _ => SeriesType::Bar, // Default to bar
-    };
-
-    Ok((input, Series { series_type, data }))
-}
-
-fn chart_content(
-    input: &str,
-) -> IResult<
-    &str,
-    (
-        Option<String>,
-        Option<Vec<String>>,
-        XAxis,
-        YAxis,
-        Vec<Series>,
-    ),
-> {
-    let (input, title) = xy_header(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, legend) = opt(preceded(tuple((tag("legend"), space0)), legend_line))(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, x_axis) = x_axis_line(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, y_axis) = y_axis_line(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, series) = separated_list0(multispace0, series_line)(input)?;
-
-    Ok((input, (title, legend, x_axis, y_axis, series)))
-}
+use super::{parse_xychart_content, XYChart};
+use crate::common::config_line;
 
 pub fn parse_xychart(input: &str) -> IResult<&str, XYChart> {
     let (input, config) = opt(preceded(multispace0, config_line))(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, (title, legend, x_axis, y_axis, series)) = chart_content(input)?;
-    let (input, _) = multispace0(input)?;
-
-    Ok((
-        input,
-        XYChart {
-            config,
-            title,
-            legend,
-            x_axis,
-            y_axis,
-            series,
-        },
-    ))
+    parse_xychart_content(input, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_parser::parse_label;
+    use crate::common::string_parser::{parse_label, parse_labels_list};
+    use crate::xychart::{AnnotationKind, AxisSide, Curve, SeriesType};
 
     #[test]
     fn test_parse_xychart() {
@@ -153,8 +44,14 @@ xychart-beta
         assert_eq!(xychart.y_axis.max, 10.0);
         assert_eq!(xychart.series.len(), 2);
         assert_eq!(xychart.series[0].series_type, SeriesType::Bar);
-        assert_eq!(xychart.series[0].data, vec![2.0, 4.0, 6.0, 8.0, 9.0]);
-        assert_eq!(xychart.series[1].data, vec![8.5, 7.0, 5.0, 3.0, 1.0]);
+        assert_eq!(
+            xychart.series[0].data,
+            vec![Some(2.0), Some(4.0), Some(6.0), Some(8.0), Some(9.0)]
+        );
+        assert_eq!(
+            xychart.series[1].data,
+            vec![Some(8.5), Some(7.0), Some(5.0), Some(3.0), Some(1.0)]
+        );
     }
 
     #[test]
@@ -207,4 +104,203 @@ xychart-beta
         assert_eq!(xychart.x_axis.labels[2], "Simple Label");
         assert_eq!(xychart.x_axis.labels[3], "UnquotedLabel");
     }
+
+    #[test]
+    fn test_parse_xychart_with_right_axis() {
+        let input = r#"xychart-beta
+  title "Story points vs cycle time"
+  x-axis [Sprint 1, Sprint 2, Sprint 3]
+  y-axis "Story points" 0 --> 60
+  y-axis right "Cycle time (days)" 0 --> 10
+  bar [40, 52, 48]
+  line right [4.5, 3, 3.5]
+"#;
+
+        let result = parse_xychart(input);
+        assert!(result.is_ok(), "Failed to parse: {:?}", result);
+
+        let (_, xychart) = result.unwrap();
+        assert_eq!(xychart.y_axis.title, "Story points");
+        let right_axis = xychart.y_axis_right.expect("right axis should be parsed");
+        assert_eq!(right_axis.title, "Cycle time (days)");
+        assert_eq!(right_axis.max, 10.0);
+        assert_eq!(xychart.series.len(), 2);
+        assert_eq!(xychart.series[0].axis, AxisSide::Left);
+        assert_eq!(xychart.series[1].series_type, SeriesType::Line);
+        assert_eq!(xychart.series[1].axis, AxisSide::Right);
+        assert_eq!(
+            xychart.series[1].data,
+            vec![Some(4.5), Some(3.0), Some(3.5)]
+        );
+    }
+
+    #[test]
+    fn test_parse_xychart_rejects_two_left_axes() {
+        let input = r#"xychart-beta
+  x-axis [A, B]
+  y-axis "One" 0 --> 10
+  y-axis left "Two" 0 --> 10
+  bar [1, 2]
+"#;
+
+        assert!(parse_xychart(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_xychart_with_series_attributes() {
+        let input = r##"xychart-beta
+  x-axis [Day 1, Day 2, Day 3]
+  y-axis "Story points" 0 --> 120
+  line "Target" {color: #cccccc, stroke: dashed} [12, 24, 36]
+  line right 'Done' {color: #038411, points: square} [0, 5, 15]
+  bar [1, 2, 3]
+"##;
+
+        let result = parse_xychart(input);
+        assert!(result.is_ok(), "Failed to parse: {:?}", result);
+
+        let (_, xychart) = result.unwrap();
+        assert_eq!(xychart.series.len(), 3);
+        assert_eq!(xychart.series[0].name, Some("Target".to_string()));
+        assert_eq!(xychart.series[0].color, Some("#cccccc".to_string()));
+        assert_eq!(xychart.series[0].stroke, Some("dashed".to_string()));
+        assert_eq!(xychart.series[0].points, None);
+        assert_eq!(xychart.series[1].name, Some("Done".to_string()));
+        assert_eq!(xychart.series[1].axis, AxisSide::Right);
+        assert_eq!(xychart.series[1].points, Some("square".to_string()));
+        assert_eq!(xychart.series[2].name, None);
+        assert_eq!(xychart.series[2].color, None);
+    }
+
+    #[test]
+    fn test_parse_xychart_rejects_unknown_series_attribute() {
+        let input = r#"xychart-beta
+  x-axis [A, B]
+  y-axis "Values" 0 --> 10
+  line {colour: red} [1, 2]
+"#;
+
+        assert!(parse_xychart(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_xychart_with_areas_and_curves() {
+        let input = r#"xychart-beta
+  x-axis [Mon, Tue, Wed]
+  y-axis "Items" 0 --> 40
+  stacked-area "Done" [1, 3, 5]
+  stacked-area "In Progress" {curve: step, opacity: 0.3} [4, 5, 6]
+  area {curve: monotone} [2, 4, 8]
+  line [1, 2, 3]
+"#;
+
+        let result = parse_xychart(input);
+        assert!(result.is_ok(), "Failed to parse: {:?}", result);
+
+        let (_, xychart) = result.unwrap();
+        assert_eq!(xychart.series.len(), 4);
+        assert_eq!(xychart.series[0].series_type, SeriesType::StackedArea);
+        assert_eq!(xychart.series[1].series_type, SeriesType::StackedArea);
+        assert_eq!(xychart.series[1].curve, Some(Curve::Step));
+        assert_eq!(xychart.series[1].opacity, Some(0.3));
+        assert_eq!(xychart.series[2].series_type, SeriesType::Area);
+        assert_eq!(xychart.series[2].curve, Some(Curve::Monotone));
+        assert_eq!(xychart.series[3].series_type, SeriesType::Line);
+        assert_eq!(xychart.series[3].curve, None);
+    }
+
+    #[test]
+    fn test_parse_xychart_rejects_invalid_opacity() {
+        let input = r#"xychart-beta
+  x-axis [A, B]
+  y-axis "Values" 0 --> 10
+  area {opacity: half} [1, 2]
+"#;
+
+        assert!(parse_xychart(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_xychart_rejects_unknown_attribute_values() {
+        for attributes in ["{curve: smooth}", "{stroke: wiggly}", "{points: circle}"] {
+            let input = format!(
+                "xychart-beta\n  x-axis [A, B]\n  y-axis \"Values\" 0 --> 10\n  line {} [1, 2]\n",
+                attributes
+            );
+            assert!(
+                parse_xychart(&input).is_err(),
+                "{} was accepted",
+                attributes
+            );
+        }
+
+        let input = r#"xychart-beta
+  x-axis [A, B]
+  y-axis "Values" 0 --> 10
+  hline 5 {stroke: wiggly}
+"#;
+        assert!(parse_xychart(input).is_err());
+    }
+
+    #[test]
+    fn test_parse_xychart_with_gaps() {
+        let input = r#"xychart-beta
+  x-axis [Day 1, Day 2, Day 3, Day 4, Day 5]
+  y-axis "Story points" 0 --> 50
+  line [5, null, 36, _, 40]
+  bar [_, 2, 3]
+"#;
+
+        let result = parse_xychart(input);
+        assert!(result.is_ok(), "Failed to parse: {:?}", result);
+
+        let (_, xychart) = result.unwrap();
+        assert_eq!(
+            xychart.series[0].data,
+            vec![Some(5.0), None, Some(36.0), None, Some(40.0)]
+        );
+        assert_eq!(xychart.series[1].data, vec![None, Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn test_parse_xychart_with_annotations() {
+        let input = r##"xychart-beta
+  x-axis [Day 1, Day 2, Day 3]
+  y-axis "Story points" 0 --> 120
+  y-axis right "Days" 0 --> 10
+  hline 100 "Scope" {color: #aaaaaa, stroke: dashed}
+  line [10, 20, 30]
+  vline "Day 2" "Today"
+  band right 2 --> 4 {opacity: 0.2}
+  bar [1, 2, 3]
+"##;
+
+        let result = parse_xychart(input);
+        assert!(result.is_ok(), "Failed to parse: {:?}", result);
+
+        let (_, xychart) = result.unwrap();
+        assert_eq!(xychart.series.len(), 2);
+        assert_eq!(xychart.annotations.len(), 3);
+
+        let hline = &xychart.annotations[0];
+        assert_eq!(hline.kind, AnnotationKind::HorizontalLine { value: 100.0 });
+        assert_eq!(hline.label, Some("Scope".to_string()));
+        assert_eq!(hline.color, Some("#aaaaaa".to_string()));
+        assert_eq!(hline.stroke, Some("dashed".to_string()));
+
+        let vline = &xychart.annotations[1];
+        assert_eq!(
+            vline.kind,
+            AnnotationKind::VerticalLine {
+                category: "Day 2".to_string()
+            }
+        );
+        assert_eq!(vline.label, Some("Today".to_string()));
+
+        let band = &xychart.annotations[2];
+        assert_eq!(band.kind, AnnotationKind::Band { from: 2.0, to: 4.0 });
+        assert_eq!(band.axis, AxisSide::Right);
+        assert_eq!(band.label, None);
+        assert_eq!(band.opacity, Some(0.2));
+    }
 }
@@ -1,5 +1,7 @@
-use super::{SeriesType, XYChart};
-use crate::common::renderer::{calculate_legend_width, render_legend, LegendConfig};
+use super::{Annotation, AnnotationKind, AxisSide, Curve, Series, SeriesType, XYChart, YAxis};
+use crate::common::renderer::{
+    layout_legend, parse_legend_position, render_legend, LegendConfig, LegendPosition,
+};
 use crate::font::{load_system_font_bytes, measure_text_height, measure_text_width};
 use svg::node::element::{Group, Path, Rectangle, Style, Text};
 use svg::Document;
@@ -9,12 +11,31 @@ const DEFAULT_COLORS: [&str; 10] = [
     "#bcbd22", "#17becf",
 ];
 
+/// The largest share of the image height that rotated X-axis labels can take up
+const MAX_X_LABEL_SPACE_RATIO: f64 = 0.25;
+/// The most categories between each shown X-axis label when labels are thinned out
+const MAX_X_LABEL_STEP: usize = 5;
+/// The most lines that an X-axis label can be wrapped onto
+const MAX_X_LABEL_LINES: usize = 3;
+/// Minimum gap between X-axis labels in pixels
+const MIN_X_LABEL_GAP: f64 = 5.0;
+/// Distance between wrapped lines as a multiple of the text height
+const LINE_SPACING: f64 = 1.1;
+/// The most ticks drawn on a y-axis, a tick step that needs more falls back to the tick count
+const MAX_Y_TICKS: usize = 200;
+
 pub fn render_xychart_svg(
     xychart: &XYChart,
     default_width: u32,
     height: u32,
     font_name: &str,
 ) -> (Document, u32, u32) {
+    let xychart = &XYChart {
+        y_axis: drawable_axis(&xychart.y_axis),
+        y_axis_right: xychart.y_axis_right.as_ref().map(drawable_axis),
+        ..xychart.clone()
+    };
+
     // Use config width if present, otherwise use default
     let width = xychart
         .config
@@ -34,6 +55,7 @@ pub fn render_xychart_svg(
         .parse::<f32>()
         .unwrap_or(16.0);
     let axis_title_font_size = 16.0; // Match mermaid axis title size
+    let annotation_font_size = 14.0;
     let legend_font_size = get_theme_variable(xychart, "xyChart.legendFontSize", "17")
         .parse::<f32>()
         .unwrap_or(17.0);
@@ -46,12 +68,11 @@ pub fn render_xychart_svg(
         ..Default::default()
     };
 
-    let (legend_width, chart_to_legend_gap) = if let Some(ref legend_labels) = xychart.legend {
-        let width = calculate_legend_width(legend_labels, &font_data, &legend_config);
-        (width, 20.0) // Width + gap between chart and legend
-    } else {
-        (0.0, 0.0)
-    };
+    let legend_position = parse_legend_position(get_theme_variable(
+        xychart,
+        "xyChart.legendPosition",
+        "right",
+    ));
 
     // Calculate title height and spacing
     let (title_height, title_to_chart_gap) = if xychart.title.is_some() {
@@ -65,50 +86,61 @@ pub fn render_xychart_svg(
         (0.0, 0.0) // No title, no gap
     };
 
-    // Calculate the width of the longest Y-axis label
-    let num_ticks = 11; // 0 to 10
-    let max_y_label_width = if let Some(ref font_data) = font_data {
-        let mut max_width = 0.0f32;
-        for i in 0..num_ticks {
-            let value = xychart.y_axis.max
-                - (i as f64 * (xychart.y_axis.max - xychart.y_axis.min) / (num_ticks - 1) as f64);
-            let label_text = format!("{}", value as i32);
-            let width = measure_text_width(&label_text, font_data, label_font_size);
-            max_width = max_width.max(width);
+    // Lay out the legend if present, legends beside the chart wrap into columns when they are
+    // taller than the chart and legends above or below it wrap into rows
+    let legend_entries = get_legend_entries(xychart);
+    let legend_layout = legend_entries.as_ref().map(|(legend_labels, _)| {
+        layout_legend(
+            legend_labels,
+            &font_data,
+            &legend_config,
+            legend_position,
+            width as f64 - (margin * 2.0),
+            height as f64 - (margin * 2.0) - title_height - title_to_chart_gap,
+        )
+    });
+    let legend_position = legend_layout
+        .as_ref()
+        .map_or(legend_position, |layout| layout.position);
+    let chart_to_legend_gap = 20.0;
+    // The horizontal and vertical space taken up by the legend
+    let (legend_space_x, legend_space_y) = match legend_layout {
+        Some(ref layout) if layout.rows > 0 => {
+            if legend_position.is_beside() {
+                (layout.width + chart_to_legend_gap, 0.0)
+            } else {
+                (0.0, layout.height + chart_to_legend_gap)
+            }
         }
-        max_width as f64
-    } else {
-        // Fallback estimation
-        label_font_size as f64 * 0.6 * 2.0 // Assume max 2 characters
+        _ => (0.0, 0.0),
     };
 
-    // Check if we'll need vertical labels to calculate proper spacing
-    let should_use_vertical_labels = if let Some(ref font_data) = font_data {
-        let num_categories = xychart.x_axis.labels.len();
-        let estimated_category_width =
-            (width as f64 - (margin * 2.0) - (max_y_label_width + 35.0)) / num_categories as f64;
-        check_label_overlap(
-            &xychart.x_axis.labels,
-            estimated_category_width,
-            font_data,
-            label_font_size,
-        )
-    } else {
-        false
+    // Calculate the ticks on each Y-axis, a tick step takes precedence over the tick count
+    let tick_count = get_theme_variable(xychart, "xyChart.tickCount", "11")
+        .parse::<usize>()
+        .unwrap_or(11)
+        .max(2);
+    let parse_tick_step = |key: &str| {
+        get_theme_variable(xychart, key, "")
+            .parse::<f64>()
+            .ok()
+            .filter(|step| *step > 0.0)
     };
+    let y_ticks = calculate_ticks(
+        &xychart.y_axis,
+        tick_count,
+        parse_tick_step("xyChart.tickStep"),
+    );
+    let right_y_ticks = xychart
+        .y_axis_right
+        .as_ref()
+        .map(|axis| calculate_ticks(axis, tick_count, parse_tick_step("xyChart.rightTickStep")));
 
-    // Calculate the maximum label width for vertical labels
-    let max_x_label_width = if should_use_vertical_labels && font_data.is_some() {
-        let font_data = font_data.as_ref().unwrap();
-        xychart
-            .x_axis
-            .labels
-            .iter()
-            .map(|label| measure_text_width(label, font_data, label_font_size) as f64)
-            .fold(0.0, f64::max)
-    } else {
-        0.0
-    };
+    // Calculate the width of the longest label on each Y-axis
+    let max_y_label_width = calculate_y_label_width(&y_ticks, &font_data, label_font_size);
+    let max_right_y_label_width = right_y_ticks
+        .as_ref()
+        .map(|ticks| calculate_y_label_width(ticks, &font_data, label_font_size));
 
     // Space needed for axes - using consistent spacing components
     let label_to_axis_gap = 10.0; // Gap between labels and axis line
@@ -116,21 +148,64 @@ pub fn render_xychart_svg(
     let axis_title_width = 20.0; // Approximate width needed for rotated axis title text
     let y_axis_label_space =
         max_y_label_width + label_to_axis_gap + title_to_labels_gap + axis_title_width;
-    let x_axis_label_space = if should_use_vertical_labels {
-        max_x_label_width + 20.0 // Width of longest label + margin
+    let right_y_axis_label_space = max_right_y_label_width.map_or(0.0, |label_width| {
+        label_width + label_to_axis_gap + title_to_labels_gap + axis_title_width
+    });
+
+    // Calculate available width for the chart area
+    let chart_width = width as f64
+        - (margin * 2.0)
+        - y_axis_label_space
+        - right_y_axis_label_space
+        - legend_space_x;
+    let chart_left = if legend_position == LegendPosition::Left {
+        margin + legend_space_x + y_axis_label_space
     } else {
-        40.0 // Space for horizontal X-axis labels
+        margin + y_axis_label_space
     };
+    let num_categories = xychart.x_axis.labels.len();
+    let category_width = chart_width / num_categories as f64;
+
+    let label_height = if let Some(ref font_data) = font_data {
+        measure_text_height(font_data, label_font_size) as f64
+    } else {
+        label_font_size as f64
+    };
+
+    // Choose how to lay out the X-axis labels so they don't overlap each other
+    let measure_label = |text: &str| measure_label_width(text, &font_data, label_font_size);
+    let x_label_layout = choose_x_label_layout(
+        get_theme_variable(xychart, "xyChart.xAxisLabelStrategy", "auto"),
+        &xychart.x_axis.labels,
+        &XLabelSpace {
+            category_width,
+            label_height,
+            first_label_x: chart_left + category_width / 2.0,
+            max_height: height as f64 * MAX_X_LABEL_SPACE_RATIO,
+        },
+        &measure_label,
+    );
+    let x_axis_label_space = calculate_x_label_space(
+        &x_label_layout,
+        &xychart.x_axis.labels,
+        label_height,
+        &measure_label,
+    );
 
-    // Calculate available space for the chart area
-    let chart_width =
-        width as f64 - (margin * 2.0) - y_axis_label_space - legend_width - chart_to_legend_gap;
-    let chart_height =
-        height as f64 - (margin * 2.0) - title_height - title_to_chart_gap - x_axis_label_space;
+    // Calculate available height for the chart area
+    let chart_height = height as f64
+        - (margin * 2.0)
+        - title_height
+        - title_to_chart_gap
+        - x_axis_label_space
+        - legend_space_y;
 
     // Calculate positions
-    let chart_left = margin + y_axis_label_space;
-    let chart_top = margin + title_height + title_to_chart_gap;
+    let chart_top = if legend_position == LegendPosition::Top {
+        margin + title_height + title_to_chart_gap + legend_space_y
+    } else {
+        margin + title_height + title_to_chart_gap
+    };
     let chart_bottom = chart_top + chart_height;
     let chart_right = chart_left + chart_width;
 
@@ -145,15 +220,23 @@ pub fn render_xychart_svg(
         );
 
     // Add CSS styles
-    let style = Style::new(&format!(
+    let style = Style::new(format!(
         r#"
             .chart-title {{ text-anchor: middle; font-size: {}px; fill: #131300; font-family: "{}", sans-serif; }}
             .axis-line {{ stroke: #131300; stroke-width: 2px; fill: none; }}
             .axis-label {{ font-size: {}px; fill: #131300; font-family: "{}", sans-serif; }}
             .axis-title {{ font-size: {}px; fill: #131300; font-family: "{}", sans-serif; }}
             .tick {{ stroke: #131300; stroke-width: 2px; fill: none; }}
+            .annotation-label {{ font-size: {}px; font-family: "{}", sans-serif; }}
         "#,
-        title_font_size, font_name, label_font_size, font_name, axis_title_font_size, font_name
+        title_font_size,
+        font_name,
+        label_font_size,
+        font_name,
+        axis_title_font_size,
+        font_name,
+        annotation_font_size,
+        font_name
     ));
     document = document.add(style);
 
@@ -185,27 +268,161 @@ pub fn render_xychart_svg(
     }
 
     // Calculate bar positioning for stacked bars
-    let num_categories = xychart.x_axis.labels.len();
-    let category_width = chart_width / num_categories as f64;
     let bar_width = category_width * 0.8; // Single width for stacked bars
 
-    // Y-axis scaling
-    let y_range = xychart.y_axis.max - xychart.y_axis.min;
-    let y_scale = chart_height / y_range;
+    // Y-axis scaling, each series is scaled against the axis it is assigned to
+    let value_to_y = |axis: &YAxis, value: f64| {
+        chart_bottom - (value - axis.min) * chart_height / (axis.max - axis.min)
+    };
 
     // Create chart plot group
     let mut plot_group = Group::new().set("class", "plot");
 
-    // Render bars first (so lines appear on top)
+    // The x position of the middle of each category
+    let category_x =
+        |data_idx: usize| chart_left + data_idx as f64 * category_width + category_width / 2.0;
+
+    // The values the line of each series is drawn through, for stacked areas this is the top
+    // of the stack rather than the series data
+    let mut line_values: Vec<Vec<Option<f64>>> = xychart
+        .series
+        .iter()
+        .map(|series| series.data.iter().take(num_categories).copied().collect())
+        .collect();
+
+    // Running totals of stacked areas for the left and right axes, along with the curve of the
+    // top layer so the next layer's bottom edge follows it exactly
+    let mut stack_totals: [Option<(Vec<f64>, Curve)>; 2] = [None, None];
+
+    // Render gridlines at the left axis ticks behind everything else
+    if get_theme_variable(xychart, "xyChart.showGrid", "false") == "true" {
+        let grid_color = get_theme_variable(xychart, "xyChart.gridColor", "#e0e0e0");
+        let mut grid_group = Group::new().set("class", "grid");
+        for (value, _) in &y_ticks {
+            let y = value_to_y(&xychart.y_axis, *value);
+            let mut gridline = Path::new()
+                .set(
+                    "d",
+                    format!("M {},{} L {},{}", chart_left, y, chart_right, y),
+                )
+                .set("stroke", grid_color)
+                .set("stroke-width", "1")
+                .set("fill", "none");
+            match get_theme_variable(xychart, "xyChart.gridStyle", "solid") {
+                "dashed" => gridline = gridline.set("stroke-dasharray", "5,5"),
+                "dotted" => gridline = gridline.set("stroke-dasharray", "1,3"),
+                _ => {}
+            }
+            grid_group = grid_group.add(gridline);
+        }
+        plot_group = plot_group.add(grid_group);
+    }
+
+    // Render reference lines and bands behind the series
+    for annotation in &xychart.annotations {
+        let axis = match (&annotation.axis, &xychart.y_axis_right) {
+            (AxisSide::Right, Some(right_axis)) => right_axis,
+            _ => &xychart.y_axis,
+        };
+        plot_group = plot_group.add(render_annotation(
+            annotation,
+            axis,
+            &xychart.x_axis.labels,
+            (chart_left, chart_top, chart_right, chart_bottom),
+            annotation_font_size,
+        ));
+    }
+
+    // Render areas first so bars and lines are drawn over them
+    for (series_idx, series) in xychart.series.iter().enumerate() {
+        let stacked = match series.series_type {
+            SeriesType::Area => false,
+            SeriesType::StackedArea => true,
+            _ => continue,
+        };
+        let axis = get_axis_for_series(xychart, series);
+        let curve = get_curve(series);
+        let values = &series.data[..series.data.len().min(num_categories)];
+
+        // Gaps in a stacked area add nothing to the stack
+        let (tops, bottoms, bottom_curve) = if stacked {
+            let stack = &mut stack_totals[get_stack_index(xychart, series)];
+            let (bottoms, bottom_curve): (Vec<f64>, Curve) = match stack {
+                Some((totals, top_curve)) => (
+                    totals.iter().take(values.len()).copied().collect(),
+                    *top_curve,
+                ),
+                None => (vec![axis.min; values.len()], Curve::Linear),
+            };
+            let (totals, top_curve) =
+                stack.get_or_insert_with(|| (vec![0.0; num_categories], curve));
+            for (total, value) in totals.iter_mut().zip(values) {
+                *total += value.unwrap_or(0.0);
+            }
+            *top_curve = curve;
+            let tops = values
+                .iter()
+                .zip(totals.iter())
+                .map(|(value, &total)| value.map(|_| total))
+                .collect();
+            (tops, bottoms, bottom_curve)
+        } else {
+            (values.to_vec(), vec![axis.min; values.len()], Curve::Linear)
+        };
+
+        // Each unbroken run of values is drawn as a separate shape, tracing the top of the
+        // area forwards then its bottom backwards
+        let mut path_data = String::new();
+        for run in find_unbroken_runs(&tops) {
+            let top_points: Vec<(f64, f64)> = run
+                .iter()
+                .map(|&(data_idx, value)| (category_x(data_idx), value_to_y(axis, value)))
+                .collect();
+            let bottom_points: Vec<(f64, f64)> = run
+                .iter()
+                .map(|&(data_idx, _)| (category_x(data_idx), value_to_y(axis, bottoms[data_idx])))
+                .collect();
+
+            if let Some(last_bottom) = bottom_points.last() {
+                if !path_data.is_empty() {
+                    path_data.push(' ');
+                }
+                path_data.push_str(&curve_path(&top_points, curve));
+                path_data.push_str(&format!(" L {},{}", last_bottom.0, last_bottom.1));
+                for segment in curve_segments(&bottom_points, bottom_curve).iter().rev() {
+                    path_data.push_str(&segment.reversed().path_command());
+                }
+                path_data.push_str(" Z");
+            }
+        }
+
+        if !path_data.is_empty() {
+            plot_group = plot_group.add(
+                Path::new()
+                    .set("d", path_data)
+                    .set("fill", get_color_for_series(xychart, series_idx))
+                    .set("fill-opacity", get_area_opacity(xychart, series))
+                    .set("stroke", "none")
+                    .set("class", format!("area-plot-{}", series_idx)),
+            );
+        }
+
+        line_values[series_idx] = tops;
+    }
+
+    // Render bars next (so lines appear on top)
     for data_idx in 0..num_categories {
         let mut bars_for_position: Vec<(usize, f64, &str)> = Vec::new();
 
-        // Collect all bars for this x position
+        // Collect all bars for this x position, measuring their height in pixels since
+        // bars on different axes have different scales, gaps are skipped
         for (series_idx, series) in xychart.series.iter().enumerate() {
             if let SeriesType::Bar = series.series_type {
-                if data_idx < series.data.len() {
-                    let color = get_color_for_series(&xychart, series_idx);
-                    bars_for_position.push((series_idx, series.data[data_idx], color));
+                if let Some(&Some(value)) = series.data.get(data_idx) {
+                    let color = get_color_for_series(xychart, series_idx);
+                    let axis = get_axis_for_series(xychart, series);
+                    let bar_height = chart_bottom - value_to_y(axis, value);
+                    bars_for_position.push((series_idx, bar_height, color));
                 }
             }
         }
@@ -215,10 +432,9 @@ pub fn render_xychart_svg(
             .sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
         // Render bars for this position (tallest to shortest)
-        for (series_idx, value, color) in bars_for_position {
+        for (series_idx, bar_height, color) in bars_for_position {
             let x =
                 chart_left + data_idx as f64 * category_width + (category_width - bar_width) / 2.0;
-            let bar_height = (value - xychart.y_axis.min) * y_scale;
             let y = chart_bottom - bar_height;
 
             plot_group = plot_group.add(
@@ -235,29 +451,30 @@ pub fn render_xychart_svg(
         }
     }
 
-    // Render lines second (so they appear on top of bars)
+    // Render lines last (so they appear on top of bars), areas are outlined by a line too
     for (series_idx, series) in xychart.series.iter().enumerate() {
-        if let SeriesType::Line = series.series_type {
-            let color = get_color_for_series(&xychart, series_idx);
-            let mut path_data = String::new();
-
-            for (data_idx, &value) in series.data.iter().enumerate() {
-                if data_idx >= num_categories {
-                    break;
-                }
-
-                let x = chart_left + data_idx as f64 * category_width + category_width / 2.0;
-                let y = chart_bottom - (value - xychart.y_axis.min) * y_scale;
-
-                if data_idx == 0 {
-                    path_data.push_str(&format!("M {},{}", x, y));
-                } else {
-                    path_data.push_str(&format!(" L {},{}", x, y));
-                }
-            }
-
-            if !path_data.is_empty() {
-                let stroke_style = get_stroke_style(&xychart, series_idx);
+        if let SeriesType::Line | SeriesType::Area | SeriesType::StackedArea = series.series_type {
+            let color = get_color_for_series(xychart, series_idx);
+            let axis = get_axis_for_series(xychart, series);
+
+            // The line is broken at gaps by starting a new subpath for each unbroken run
+            let runs: Vec<Vec<(f64, f64)>> = find_unbroken_runs(&line_values[series_idx])
+                .iter()
+                .map(|run| {
+                    run.iter()
+                        .map(|&(data_idx, value)| (category_x(data_idx), value_to_y(axis, value)))
+                        .collect()
+                })
+                .collect();
+
+            if !runs.is_empty() {
+                let path_data = runs
+                    .iter()
+                    .map(|points| curve_path(points, get_curve(series)))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                let stroke_style = get_stroke_style(xychart, series_idx);
                 let mut line_path = Path::new()
                     .set("d", path_data)
                     .set("stroke", color)
@@ -273,15 +490,8 @@ pub fn render_xychart_svg(
             }
 
             // Draw plot points if specified
-            if let Some(shape) = get_plot_point_shape(&xychart, series_idx) {
-                for (data_idx, &value) in series.data.iter().enumerate() {
-                    if data_idx >= num_categories {
-                        break;
-                    }
-
-                    let x = chart_left + data_idx as f64 * category_width + category_width / 2.0;
-                    let y = chart_bottom - (value - xychart.y_axis.min) * y_scale;
-
+            if let Some(shape) = get_plot_point_shape(xychart, series_idx) {
+                for &(x, y) in runs.iter().flatten() {
                     match shape {
                         "square" => {
                             plot_group = plot_group.add(
@@ -336,13 +546,6 @@ pub fn render_xychart_svg(
         ),
     ));
 
-    // Calculate label height for vertical labels if needed
-    let label_height = if let Some(ref font_data) = font_data {
-        measure_text_height(font_data, label_font_size) as f64
-    } else {
-        label_font_size as f64
-    };
-
     // X-axis labels and ticks
     let mut x_labels_group = Group::new().set("class", "label");
     let mut x_ticks_group = Group::new().set("class", "ticks");
@@ -350,33 +553,47 @@ pub fn render_xychart_svg(
     for (i, label) in xychart.x_axis.labels.iter().enumerate() {
         let x = chart_left + i as f64 * category_width + category_width / 2.0;
 
-        // Label - adjust positioning based on orientation
-        if should_use_vertical_labels {
-            x_labels_group = x_labels_group.add(
-                Text::new(label)
-                    .set("class", "axis-label")
-                    .set("x", x)
-                    .set("y", chart_bottom + 10.0 + label_height / 2.0)
-                    .set("text-anchor", "end")
-                    .set("dominant-baseline", "middle")
-                    .set(
-                        "transform",
-                        format!(
-                            "rotate(-90, {}, {})",
-                            x,
-                            chart_bottom + 10.0 + label_height / 2.0
-                        ),
-                    ),
-            );
-        } else {
-            x_labels_group = x_labels_group.add(
-                Text::new(label)
-                    .set("class", "axis-label")
-                    .set("x", x)
-                    .set("y", chart_bottom + 20.0)
-                    .set("text-anchor", "middle")
-                    .set("dominant-baseline", "text-before-edge"),
-            );
+        // Label - adjust positioning based on the layout
+        match x_label_layout {
+            XLabelLayout::Horizontal => {
+                x_labels_group = x_labels_group.add(
+                    Text::new(label)
+                        .set("class", "axis-label")
+                        .set("x", x)
+                        .set("y", chart_bottom + 20.0)
+                        .set("text-anchor", "middle")
+                        .set("dominant-baseline", "text-before-edge"),
+                );
+            }
+            XLabelLayout::Rotated { angle, step } => {
+                if i % step == 0 {
+                    let y = chart_bottom + 10.0 + label_height / 2.0;
+                    x_labels_group = x_labels_group.add(
+                        Text::new(label)
+                            .set("class", "axis-label")
+                            .set("x", x)
+                            .set("y", y)
+                            .set("text-anchor", "end")
+                            .set("dominant-baseline", "middle")
+                            .set("transform", format!("rotate(-{}, {}, {})", angle, x, y)),
+                    );
+                }
+            }
+            XLabelLayout::Wrapped(ref wrapped_labels) => {
+                for (line_idx, line) in wrapped_labels[i].iter().enumerate() {
+                    x_labels_group = x_labels_group.add(
+                        Text::new(line)
+                            .set("class", "axis-label")
+                            .set("x", x)
+                            .set(
+                                "y",
+                                chart_bottom + 20.0 + line_idx as f64 * label_height * LINE_SPACING,
+                            )
+                            .set("text-anchor", "middle")
+                            .set("dominant-baseline", "text-before-edge"),
+                    );
+                }
+            }
         }
 
         // Tick
@@ -396,8 +613,96 @@ pub fn render_xychart_svg(
     x_axis_group = x_axis_group.add(x_ticks_group);
     main_group = main_group.add(x_axis_group);
 
-    // Y-axis
-    let mut y_axis_group = Group::new().set("class", "left-axis");
+    // Y-axes
+    main_group = main_group.add(render_y_axis(
+        &xychart.y_axis,
+        &AxisSide::Left,
+        chart_left,
+        chart_top,
+        chart_height,
+        &y_ticks,
+        max_y_label_width,
+    ));
+
+    if let (Some(right_axis), Some(right_y_ticks), Some(max_right_y_label_width)) = (
+        &xychart.y_axis_right,
+        &right_y_ticks,
+        max_right_y_label_width,
+    ) {
+        main_group = main_group.add(render_y_axis(
+            right_axis,
+            &AxisSide::Right,
+            chart_right,
+            chart_top,
+            chart_height,
+            right_y_ticks,
+            max_right_y_label_width,
+        ));
+    }
+
+    document = document.add(main_group);
+
+    // Add legend if present
+    if let (Some((ref legend_labels, ref colors)), Some(ref layout)) =
+        (&legend_entries, &legend_layout)
+    {
+        // Calculate legend position
+        let legend_position = match legend_position {
+            LegendPosition::Right => Some((
+                width as f64 - margin - layout.width,
+                chart_top + (chart_height / 2.0) - layout.height / 2.0,
+            )),
+            LegendPosition::Left => Some((
+                margin,
+                chart_top + (chart_height / 2.0) - layout.height / 2.0,
+            )),
+            LegendPosition::Top => Some((
+                (width as f64 - layout.width) / 2.0,
+                chart_top - legend_space_y,
+            )),
+            LegendPosition::Bottom => Some((
+                (width as f64 - layout.width) / 2.0,
+                chart_bottom + x_axis_label_space + chart_to_legend_gap,
+            )),
+            LegendPosition::None => None,
+        };
+
+        if let Some((legend_x, legend_y)) = legend_position {
+            document = document.add(render_legend(
+                legend_labels,
+                colors,
+                legend_x,
+                legend_y,
+                layout,
+                &legend_config,
+            ));
+        }
+    }
+
+    (document, width, height)
+}
+
+/// Render a y-axis line with its ticks, labels and title, the labels and title are placed
+/// on the outside of the chart for the given side
+fn render_y_axis(
+    axis: &YAxis,
+    side: &AxisSide,
+    axis_x: f64,
+    chart_top: f64,
+    chart_height: f64,
+    ticks: &[(f64, String)],
+    max_label_width: f64,
+) -> Group {
+    let label_to_axis_gap = 10.0; // Gap between labels and axis line
+    let title_to_labels_gap = 12.0; // Visual gap between title and the nearest edge of widest label
+
+    // Direction pointing away from the chart area
+    let (class, outward, label_anchor, title_rotation) = match side {
+        AxisSide::Left => ("left-axis", -1.0, "end", 270),
+        AxisSide::Right => ("right-axis", 1.0, "start", 90),
+    };
+
+    let mut y_axis_group = Group::new().set("class", class);
 
     // Y-axis line
     y_axis_group = y_axis_group.add(Group::new().set("class", "axisl-line").add(
@@ -405,7 +710,10 @@ pub fn render_xychart_svg(
             "d",
             format!(
                 "M {},{} L {},{}",
-                chart_left, chart_top, chart_left, chart_bottom
+                axis_x,
+                chart_top,
+                axis_x,
+                chart_top + chart_height
             ),
         ),
     ));
@@ -414,27 +722,29 @@ pub fn render_xychart_svg(
     let mut y_labels_group = Group::new().set("class", "label");
     let mut y_ticks_group = Group::new().set("class", "ticks");
 
-    // Generate Y-axis ticks from max to min
-    let num_ticks = 11; // 0 to 10
-    for i in 0..num_ticks {
-        let value = xychart.y_axis.max
-            - (i as f64 * (xychart.y_axis.max - xychart.y_axis.min) / (num_ticks - 1) as f64);
-        let y = chart_top + i as f64 * chart_height / (num_ticks - 1) as f64;
+    for (value, label) in ticks {
+        let y = chart_top + (axis.max - value) * chart_height / (axis.max - axis.min);
 
         // Label - position with consistent gap from axis line
         y_labels_group = y_labels_group.add(
-            Text::new(format!("{}", value as i32))
+            Text::new(label.clone())
                 .set("class", "axis-label")
-                .set("x", chart_left - label_to_axis_gap)
+                .set("x", axis_x + outward * label_to_axis_gap)
                 .set("y", y)
-                .set("text-anchor", "end")
+                .set("text-anchor", label_anchor)
                 .set("dominant-baseline", "middle"),
         );
 
         // Tick
         y_ticks_group = y_ticks_group.add(Path::new().set("class", "tick").set(
             "d",
-            format!("M {},{} L {},{}", chart_left - 1.0, y, chart_left - 6.0, y),
+            format!(
+                "M {},{} L {},{}",
+                axis_x + outward,
+                y,
+                axis_x + outward * 6.0,
+                y
+            ),
         ));
     }
 
@@ -442,16 +752,14 @@ pub fn render_xychart_svg(
     y_axis_group = y_axis_group.add(y_ticks_group);
 
     // Y-axis title - position with exact visual gap spacing
-    // We need to calculate where the labels actually end up being drawn
-    // The labels are positioned at: chart_left - label_to_axis_gap
-    // Since they're right-aligned, their left edge is at: (chart_left - label_to_axis_gap) - max_y_label_width
-    let actual_label_right_edge = chart_left - label_to_axis_gap;
-    let actual_label_left_edge = actual_label_right_edge - max_y_label_width;
-    let y_title_x = actual_label_left_edge - title_to_labels_gap;
+    // The labels are anchored at: axis_x + outward * label_to_axis_gap
+    // so their outer edge is a further max_label_width away from the axis
+    let actual_label_outer_edge = axis_x + outward * (label_to_axis_gap + max_label_width);
+    let y_title_x = actual_label_outer_edge + outward * title_to_labels_gap;
     let y_title_y = chart_top + chart_height / 2.0;
-    y_axis_group = y_axis_group.add(
+    y_axis_group.add(
         Group::new().set("class", "title").add(
-            Text::new(&xychart.y_axis.title)
+            Text::new(&axis.title)
                 .set("class", "axis-title")
                 .set("x", y_title_x)
                 .set("y", y_title_y)
@@ -459,34 +767,358 @@ pub fn render_xychart_svg(
                 .set("dominant-baseline", "text-after-edge")
                 .set(
                     "transform",
-                    format!("rotate(270, {}, {})", y_title_x, y_title_y),
+                    format!("rotate({}, {}, {})", title_rotation, y_title_x, y_title_y),
                 ),
         ),
+    )
+}
+
+/// Values are scaled by the range of their axis, so an axis whose minimum and maximum are the same
+/// is given a range of one rather than placing every value at NaN
+fn drawable_axis(axis: &YAxis) -> YAxis {
+    if axis.max != axis.min {
+        return axis.clone();
+    }
+    eprintln!(
+        "Warning: The y-axis \"{}\" starts and ends at {}, drawing it up to {}",
+        axis.title,
+        axis.min,
+        axis.min + 1.0
     );
+    YAxis {
+        max: axis.min + 1.0,
+        ..axis.clone()
+    }
+}
 
-    main_group = main_group.add(y_axis_group);
+/// Calculate the values and labels of the ticks on a y-axis from max to min. Without a step
+/// the axis is divided evenly into `tick_count - 1` parts and labels are truncated to integers,
+/// otherwise ticks are placed at multiples of the step and labelled with the step's precision
+fn calculate_ticks(axis: &YAxis, tick_count: usize, tick_step: Option<f64>) -> Vec<(f64, String)> {
+    if let Some(step) = tick_step {
+        if (axis.max - axis.min).abs() / step <= MAX_Y_TICKS as f64 {
+            let decimals = step
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, fraction)| fraction.len());
+            let first = (axis.min / step).ceil() as i64;
+            let last = (axis.max / step + 1e-9).floor() as i64;
+            return (first..=last)
+                .rev()
+                .map(|i| {
+                    let value = i as f64 * step;
+                    (value, format!("{:.*}", decimals, value))
+                })
+                .collect();
+        }
+        eprintln!(
+            "Warning: A tick step of {} would draw more than {} ticks on the y-axis, using the tick count instead",
+            step, MAX_Y_TICKS
+        );
+    }
 
-    document = document.add(main_group);
+    let tick_count = tick_count.clamp(2, MAX_Y_TICKS);
+    (0..tick_count)
+        .map(|i| {
+            let value = axis.max - (i as f64 * (axis.max - axis.min) / (tick_count - 1) as f64);
+            (value, format!("{}", value as i32))
+        })
+        .collect()
+}
 
-    // Add legend if present
-    if let Some(ref legend_labels) = xychart.legend {
-        // Collect colors for each series
-        let colors: Vec<String> = (0..legend_labels.len())
-            .map(|idx| get_color_for_series(&xychart, idx).to_string())
-            .collect();
+/// Calculate the width of the widest tick label on a y-axis
+fn calculate_y_label_width(
+    ticks: &[(f64, String)],
+    font_data: &Option<Vec<u8>>,
+    font_size: f32,
+) -> f64 {
+    if let Some(ref font_data) = font_data {
+        ticks
+            .iter()
+            .map(|(_, label)| measure_text_width(label, font_data, font_size))
+            .fold(0.0f32, f32::max) as f64
+    } else {
+        // Fallback estimation based on the longest label
+        let max_chars = ticks
+            .iter()
+            .map(|(_, label)| label.len())
+            .max()
+            .unwrap_or(0);
+        font_size as f64 * 0.6 * max_chars as f64
+    }
+}
 
-        // Calculate legend position
-        let legend_x = width as f64 - margin - legend_width;
-        let legend_y = chart_top + (chart_height / 2.0)
-            - (legend_labels.len() as f64 * legend_config.item_spacing / 2.0);
+/// Render a reference line or band with its label placed inside the plot area, the bounds
+/// of the plot area are given as (left, top, right, bottom)
+fn render_annotation(
+    annotation: &Annotation,
+    axis: &YAxis,
+    categories: &[String],
+    bounds: (f64, f64, f64, f64),
+    font_size: f64,
+) -> Group {
+    let (left, top, right, bottom) = bounds;
+    let label_padding = 4.0;
+    let value_to_y = |value: f64| {
+        let value = value.clamp(axis.min.min(axis.max), axis.max.max(axis.min));
+        bottom - (value - axis.min) * (bottom - top) / (axis.max - axis.min)
+    };
 
-        let legend_group =
-            render_legend(legend_labels, &colors, legend_x, legend_y, &legend_config);
+    let color = annotation.color.as_deref().unwrap_or("#7f7f7f");
+    let mut group = Group::new().set("class", "annotation");
+
+    // The label is positioned by each kind of annotation as (x, y, text-anchor)
+    let label_position = match &annotation.kind {
+        AnnotationKind::HorizontalLine { value } => {
+            let y = value_to_y(*value);
+            group = group.add(styled_annotation_line(
+                annotation,
+                color,
+                format!("M {},{} L {},{}", left, y, right, y),
+            ));
+            // Above the line at the right edge of the chart
+            Some((right - label_padding, y - label_padding, "end"))
+        }
+        AnnotationKind::VerticalLine { category } => {
+            match categories.iter().position(|label| label == category) {
+                Some(category_idx) => {
+                    let category_width = (right - left) / categories.len() as f64;
+                    let x = left + category_idx as f64 * category_width + category_width / 2.0;
+                    group = group.add(styled_annotation_line(
+                        annotation,
+                        color,
+                        format!("M {},{} L {},{}", x, top, x, bottom),
+                    ));
+                    // Next to the top of the line, on the left when in the right half of the chart
+                    if x > (left + right) / 2.0 {
+                        Some((x - label_padding, top + font_size, "end"))
+                    } else {
+                        Some((x + label_padding, top + font_size, "start"))
+                    }
+                }
+                None => {
+                    eprintln!(
+                        "Warning: vline category '{}' is not on the x-axis, skipping it",
+                        category
+                    );
+                    None
+                }
+            }
+        }
+        AnnotationKind::Band { from, to } => {
+            let (y1, y2) = (value_to_y(*from), value_to_y(*to));
+            let band_top = y1.min(y2);
+            group = group.add(
+                Rectangle::new()
+                    .set("x", left)
+                    .set("y", band_top)
+                    .set("width", right - left)
+                    .set("height", (y1 - y2).abs())
+                    .set("fill", color)
+                    .set("fill-opacity", annotation.opacity.unwrap_or(0.15))
+                    .set("stroke", "none"),
+            );
+            // Inside the top left corner of the band
+            Some((left + label_padding, band_top + font_size, "start"))
+        }
+    };
 
-        document = document.add(legend_group);
+    if let (Some(label), Some((x, y, anchor))) = (&annotation.label, label_position) {
+        group = group.add(
+            Text::new(label)
+                .set("class", "annotation-label")
+                .set("x", x)
+                .set("y", y)
+                .set("fill", color)
+                .set("text-anchor", anchor),
+        );
     }
 
-    (document, width, height)
+    group
+}
+
+fn styled_annotation_line(annotation: &Annotation, color: &str, path_data: String) -> Path {
+    let mut line_path = Path::new()
+        .set("d", path_data)
+        .set("stroke", color)
+        .set("stroke-width", "1.5")
+        .set("fill", "none");
+    if annotation.stroke.as_deref() == Some("dashed") {
+        line_path = line_path.set("stroke-dasharray", "5,5");
+    }
+    if let Some(opacity) = annotation.opacity {
+        line_path = line_path.set("stroke-opacity", opacity);
+    }
+    line_path
+}
+
+/// A cubic bezier segment, straight segments have control points equal to their end points
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CurveSegment {
+    from: (f64, f64),
+    control1: (f64, f64),
+    control2: (f64, f64),
+    to: (f64, f64),
+}
+
+impl CurveSegment {
+    fn line(from: (f64, f64), to: (f64, f64)) -> Self {
+        Self {
+            from,
+            control1: from,
+            control2: to,
+            to,
+        }
+    }
+
+    fn reversed(&self) -> Self {
+        Self {
+            from: self.to,
+            control1: self.control2,
+            control2: self.control1,
+            to: self.from,
+        }
+    }
+
+    /// The path command drawing this segment from its start point
+    fn path_command(&self) -> String {
+        if self.control1 == self.from && self.control2 == self.to {
+            format!(" L {},{}", self.to.0, self.to.1)
+        } else {
+            format!(
+                " C {},{} {},{} {},{}",
+                self.control1.0,
+                self.control1.1,
+                self.control2.0,
+                self.control2.1,
+                self.to.0,
+                self.to.1
+            )
+        }
+    }
+}
+
+/// Join points with segments according to the curve type
+fn curve_segments(points: &[(f64, f64)], curve: Curve) -> Vec<CurveSegment> {
+    match curve {
+        Curve::Step => points
+            .windows(2)
+            .flat_map(|pair| {
+                // Hold the value until the next point then jump to it
+                let corner = (pair[1].0, pair[0].1);
+                [
+                    CurveSegment::line(pair[0], corner),
+                    CurveSegment::line(corner, pair[1]),
+                ]
+            })
+            .collect(),
+        Curve::Monotone => monotone_segments(points),
+        Curve::Linear => points
+            .windows(2)
+            .map(|pair| CurveSegment::line(pair[0], pair[1]))
+            .collect(),
+    }
+}
+
+/// Split values into runs of consecutive values separated by gaps, each value is paired with
+/// its index
+fn find_unbroken_runs(values: &[Option<f64>]) -> Vec<Vec<(usize, f64)>> {
+    let mut runs: Vec<Vec<(usize, f64)>> = Vec::new();
+    let mut current_run = Vec::new();
+
+    for (data_idx, value) in values.iter().enumerate() {
+        match value {
+            Some(value) => current_run.push((data_idx, *value)),
+            None if !current_run.is_empty() => runs.push(std::mem::take(&mut current_run)),
+            None => {}
+        }
+    }
+    if !current_run.is_empty() {
+        runs.push(current_run);
+    }
+
+    runs
+}
+
+/// Build path data drawing a curve through the points
+fn curve_path(points: &[(f64, f64)], curve: Curve) -> String {
+    let mut path_data = match points.first() {
+        Some(first) => format!("M {},{}", first.0, first.1),
+        None => return String::new(),
+    };
+    for segment in curve_segments(points, curve) {
+        path_data.push_str(&segment.path_command());
+    }
+    path_data
+}
+
+/// Monotone cubic interpolation (Fritsch-Carlson), the curve never overshoots the points so
+/// flat stretches stay flat and the curve never leaves the range of its neighbouring points
+fn monotone_segments(points: &[(f64, f64)]) -> Vec<CurveSegment> {
+    let n = points.len();
+    if n < 3 {
+        return curve_segments(points, Curve::Linear);
+    }
+
+    let slopes: Vec<f64> = points
+        .windows(2)
+        .map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+        .collect();
+
+    let mut tangents = vec![0.0; n];
+    tangents[0] = slopes[0];
+    tangents[n - 1] = slopes[n - 2];
+    for i in 1..n - 1 {
+        let (s0, s1) = (slopes[i - 1], slopes[i]);
+        // Local extrema and flat sections get a flat tangent
+        if s0 * s1 > 0.0 {
+            let h0 = points[i].0 - points[i - 1].0;
+            let h1 = points[i + 1].0 - points[i].0;
+            let p = (s0 * h1 + s1 * h0) / (h0 + h1);
+            tangents[i] = (s0.signum() + s1.signum()) * s0.abs().min(s1.abs()).min(0.5 * p.abs());
+        }
+    }
+
+    points
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let dx = (pair[1].0 - pair[0].0) / 3.0;
+            CurveSegment {
+                from: pair[0],
+                control1: (pair[0].0 + dx, pair[0].1 + tangents[i] * dx),
+                control2: (pair[1].0 - dx, pair[1].1 - tangents[i + 1] * dx),
+                to: pair[1],
+            }
+        })
+        .collect()
+}
+
+/// Stacked areas are stacked separately for each axis
+fn get_stack_index(xychart: &XYChart, series: &Series) -> usize {
+    match (&series.axis, &xychart.y_axis_right) {
+        (AxisSide::Right, Some(_)) => 1,
+        _ => 0,
+    }
+}
+
+fn get_curve(series: &Series) -> Curve {
+    series.curve.unwrap_or_default()
+}
+
+fn get_area_opacity(xychart: &XYChart, series: &Series) -> String {
+    match series.opacity {
+        Some(opacity) => opacity.to_string(),
+        None => get_theme_variable(xychart, "xyChart.areaOpacity", "0.5").to_string(),
+    }
+}
+
+/// Series assigned to the right axis fall back to the left axis when the chart has no right axis
+fn get_axis_for_series<'a>(xychart: &'a XYChart, series: &Series) -> &'a YAxis {
+    match (&series.axis, &xychart.y_axis_right) {
+        (AxisSide::Right, Some(right_axis)) => right_axis,
+        _ => &xychart.y_axis,
+    }
 }
 
 fn get_theme_variable<'a>(xychart: &'a XYChart, key: &str, default: &'a str) -> &'a str {
@@ -498,25 +1130,218 @@ fn get_theme_variable<'a>(xychart: &'a XYChart, key: &str, default: &'a str) ->
     default
 }
 
-fn check_label_overlap(
-    labels: &[String],
+/// How the X-axis labels are laid out
+#[derive(Debug, PartialEq)]
+enum XLabelLayout {
+    Horizontal,
+    /// Labels rotated anticlockwise by `angle` degrees, only every `step`th label is shown
+    Rotated {
+        angle: f64,
+        step: usize,
+    },
+    /// The lines of each label, lines that still don't fit end with an ellipsis
+    Wrapped(Vec<Vec<String>>),
+}
+
+/// The space available to the X-axis labels
+struct XLabelSpace {
     category_width: f64,
-    font_data: &[u8],
-    font_size: f32,
+    label_height: f64,
+    /// Distance from the left edge of the image to the first label
+    first_label_x: f64,
+    /// The most vertical space that rotated labels should take up
+    max_height: f64,
+}
+
+fn measure_label_width(text: &str, font_data: &Option<Vec<u8>>, font_size: f32) -> f64 {
+    match font_data {
+        Some(font_data) => measure_text_width(text, font_data, font_size) as f64,
+        // Fallback estimation based on the number of characters
+        None => text.chars().count() as f64 * font_size as f64 * 0.6,
+    }
+}
+
+/// Choose how to lay out the X-axis labels. The `auto` strategy keeps the labels horizontal
+/// when they fit, then tries rotating them by 45 degrees, then showing every Nth rotated label,
+/// then wrapping them onto multiple lines and as a last resort truncates them with an ellipsis.
+/// The `vertical` strategy rotates every label by 90 degrees when any label doesn't fit.
+fn choose_x_label_layout(
+    strategy: &str,
+    labels: &[String],
+    space: &XLabelSpace,
+    measure: &dyn Fn(&str) -> f64,
+) -> XLabelLayout {
+    let fits_horizontally = labels
+        .iter()
+        .all(|label| measure(label) + MIN_X_LABEL_GAP <= space.category_width);
+    let wrap_labels = || {
+        XLabelLayout::Wrapped(
+            labels
+                .iter()
+                .map(|label| wrap_label(label, space.category_width - MIN_X_LABEL_GAP, measure))
+                .collect(),
+        )
+    };
+
+    match strategy {
+        "vertical" if fits_horizontally => XLabelLayout::Horizontal,
+        "vertical" => XLabelLayout::Rotated {
+            angle: 90.0,
+            step: 1,
+        },
+        "angled" => XLabelLayout::Rotated {
+            angle: 45.0,
+            step: rotated_label_step(45.0, space),
+        },
+        "wrap" => wrap_labels(),
+        _ => {
+            if strategy != "auto" {
+                eprintln!(
+                    "Warning: Unknown x-axis label strategy '{}', using auto",
+                    strategy
+                );
+            }
+            if fits_horizontally {
+                return XLabelLayout::Horizontal;
+            }
+
+            let step = rotated_label_step(45.0, space);
+            if step <= MAX_X_LABEL_STEP && rotated_labels_fit(labels, 45.0, step, space, measure) {
+                XLabelLayout::Rotated { angle: 45.0, step }
+            } else {
+                wrap_labels()
+            }
+        }
+    }
+}
+
+/// The smallest step between shown labels that stops rotated labels overlapping each other
+fn rotated_label_step(angle: f64, space: &XLabelSpace) -> usize {
+    let spacing = space.category_width * angle.to_radians().sin();
+    (((space.label_height + MIN_X_LABEL_GAP) / spacing).ceil() as usize).max(1)
+}
+
+/// Check that the shown rotated labels stay within the maximum height and don't run off the
+/// left edge of the image
+fn rotated_labels_fit(
+    labels: &[String],
+    angle: f64,
+    step: usize,
+    space: &XLabelSpace,
+    measure: &dyn Fn(&str) -> f64,
 ) -> bool {
-    let min_gap = 5.0; // Minimum gap between labels in pixels
+    let radians = angle.to_radians();
+    labels.iter().enumerate().step_by(step).all(|(i, label)| {
+        let label_width = measure(label);
+        let height = rotated_label_space(label_width, space.label_height, radians);
+        let left_extent = label_width * radians.cos() + space.label_height / 2.0 * radians.sin();
+        height <= space.max_height
+            && left_extent <= space.first_label_x + i as f64 * space.category_width
+    })
+}
 
-    for label in labels.iter() {
-        let label_width = measure_text_width(label, font_data, font_size) as f64;
-        // If any label width + minimum gap exceeds category width, we need vertical labels
-        if label_width + min_gap > category_width {
-            return true;
+/// Vertical space needed below the X-axis for a label rotated by `radians`
+fn rotated_label_space(label_width: f64, label_height: f64, radians: f64) -> f64 {
+    20.0 + label_width * radians.sin() + label_height * radians.cos()
+}
+
+/// Calculate the vertical space needed below the X-axis for the labels
+fn calculate_x_label_space(
+    layout: &XLabelLayout,
+    labels: &[String],
+    label_height: f64,
+    measure: &dyn Fn(&str) -> f64,
+) -> f64 {
+    match layout {
+        XLabelLayout::Horizontal => 40.0,
+        XLabelLayout::Rotated { angle, step } => labels
+            .iter()
+            .step_by(*step)
+            .map(|label| rotated_label_space(measure(label), label_height, angle.to_radians()))
+            .fold(0.0, f64::max),
+        XLabelLayout::Wrapped(wrapped_labels) => {
+            let max_lines = wrapped_labels.iter().map(Vec::len).max().unwrap_or(0);
+            40.0 + max_lines.saturating_sub(1) as f64 * label_height * LINE_SPACING
         }
     }
-    false
+}
+
+/// Wrap a label onto lines no wider than `max_width` by breaking it between words. Words that
+/// are too long for a line, and lines beyond the maximum, are truncated with an ellipsis.
+fn wrap_label(label: &str, max_width: f64, measure: &dyn Fn(&str) -> f64) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in label.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if measure(&format!("{} {}", line, word)) <= max_width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    // Join the overflowing lines onto the last line so that it is truncated
+    if lines.len() > MAX_X_LABEL_LINES {
+        let overflow = lines.split_off(MAX_X_LABEL_LINES).join(" ");
+        if let Some(last_line) = lines.last_mut() {
+            last_line.push(' ');
+            last_line.push_str(&overflow);
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| truncate_label(line, max_width, measure))
+        .collect()
+}
+
+/// Truncate text with an ellipsis so that it is no wider than `max_width`
+fn truncate_label(text: &str, max_width: f64, measure: &dyn Fn(&str) -> f64) -> String {
+    if measure(text) <= max_width {
+        return text.to_string();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    (1..chars.len())
+        .rev()
+        .map(|len| format!("{}…", chars[..len].iter().collect::<String>().trim_end()))
+        .find(|truncated| measure(truncated) <= max_width)
+        .unwrap_or_else(|| "…".to_string())
+}
+
+/// Labels and colours for the legend, an explicit `legend` line takes precedence over
+/// series names
+fn get_legend_entries(xychart: &XYChart) -> Option<(Vec<String>, Vec<String>)> {
+    if let Some(ref legend_labels) = xychart.legend {
+        let colors = (0..legend_labels.len())
+            .map(|idx| get_color_for_series(xychart, idx).to_string())
+            .collect();
+        return Some((legend_labels.clone(), colors));
+    }
+
+    let (labels, colors): (Vec<String>, Vec<String>) = xychart
+        .series
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, series)| {
+            series
+                .name
+                .as_ref()
+                .map(|name| (name.clone(), get_color_for_series(xychart, idx).to_string()))
+        })
+        .unzip();
+
+    if labels.is_empty() {
+        None
+    } else {
+        Some((labels, colors))
+    }
 }
 
 fn get_color_for_series(xychart: &XYChart, index: usize) -> &str {
+    if let Some(color) = xychart.series.get(index).and_then(|s| s.color.as_deref()) {
+        return color;
+    }
     if let Some(config) = &xychart.config {
         // Check for xyChart.plotColorPalette
         if let Some(palette) = config.theme_variables.get("xyChart.plotColorPalette") {
@@ -530,6 +1355,9 @@ fn get_color_for_series(xychart: &XYChart, index: usize) -> &str {
 }
 
 fn get_plot_point_shape(xychart: &XYChart, index: usize) -> Option<&str> {
+    if let Some(shape) = xychart.series.get(index).and_then(|s| s.points.as_deref()) {
+        return (shape != "none").then_some(shape);
+    }
     if let Some(config) = &xychart.config {
         if let Some(plot_points) = config.theme_variables.get("xyChart.plotPoints") {
             let shapes: Vec<&str> = plot_points.split(',').map(|s| s.trim()).collect();
@@ -545,6 +1373,9 @@ fn get_plot_point_shape(xychart: &XYChart, index: usize) -> Option<&str> {
 }
 
 fn get_stroke_style(xychart: &XYChart, index: usize) -> &str {
+    if let Some(style) = xychart.series.get(index).and_then(|s| s.stroke.as_deref()) {
+        return style;
+    }
     if let Some(config) = &xychart.config {
         if let Some(stroke_styles) = config.theme_variables.get("xyChart.strokeStyles") {
             let styles: Vec<&str> = stroke_styles.split(',').map(|s| s.trim()).collect();
@@ -555,3 +1386,177 @@ fn get_stroke_style(xychart: &XYChart, index: usize) -> &str {
     }
     "solid"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(min: f64, max: f64) -> YAxis {
+        YAxis {
+            title: String::new(),
+            min,
+            max,
+        }
+    }
+
+    fn labels(ticks: &[(f64, String)]) -> Vec<&str> {
+        ticks.iter().map(|(_, label)| label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_drawable_axis() {
+        assert_eq!(drawable_axis(&axis(0.0, 10.0)), axis(0.0, 10.0));
+        assert_eq!(drawable_axis(&axis(10.0, 0.0)), axis(10.0, 0.0));
+        assert_eq!(drawable_axis(&axis(5.0, 5.0)), axis(5.0, 6.0));
+    }
+
+    #[test]
+    fn test_calculate_ticks_with_count() {
+        let ticks = calculate_ticks(&axis(0.0, 100.0), 5, None);
+        assert_eq!(labels(&ticks), vec!["100", "75", "50", "25", "0"]);
+        assert_eq!(ticks[1].0, 75.0);
+
+        assert_eq!(
+            calculate_ticks(&axis(0.0, 10.0), 100_000, None).len(),
+            MAX_Y_TICKS
+        );
+    }
+
+    #[test]
+    fn test_calculate_ticks_with_step() {
+        let ticks = calculate_ticks(&axis(0.0, 10.0), 11, Some(2.5));
+        assert_eq!(labels(&ticks), vec!["10.0", "7.5", "5.0", "2.5", "0.0"]);
+
+        // Ticks start at the first multiple of the step within the axis
+        let ticks = calculate_ticks(&axis(3.0, 10.0), 11, Some(2.0));
+        assert_eq!(labels(&ticks), vec!["10", "8", "6", "4"]);
+
+        let ticks = calculate_ticks(&axis(-0.5, 0.5), 11, Some(0.25));
+        assert_eq!(
+            labels(&ticks),
+            vec!["0.50", "0.25", "0.00", "-0.25", "-0.50"]
+        );
+    }
+
+    #[test]
+    fn test_calculate_ticks_with_too_small_step() {
+        let ticks = calculate_ticks(&axis(0.0, 1_000_000.0), 11, Some(0.0001));
+        assert_eq!(ticks.len(), 11);
+        assert_eq!(ticks[0].1, "1000000");
+    }
+
+    /// Every character is 10 pixels wide
+    fn measure(text: &str) -> f64 {
+        text.chars().count() as f64 * 10.0
+    }
+
+    fn space(category_width: f64, max_height: f64) -> XLabelSpace {
+        XLabelSpace {
+            category_width,
+            label_height: 12.0,
+            first_label_x: 100.0,
+            max_height,
+        }
+    }
+
+    fn strings(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn test_choose_x_label_layout() {
+        let short = strings(&["Jan", "Feb", "Mar"]);
+        let long = strings(&["January", "February", "March", "April"]);
+
+        assert_eq!(
+            choose_x_label_layout("auto", &short, &space(50.0, 150.0), &measure),
+            XLabelLayout::Horizontal
+        );
+        assert_eq!(
+            choose_x_label_layout("auto", &long, &space(40.0, 150.0), &measure),
+            XLabelLayout::Rotated {
+                angle: 45.0,
+                step: 1
+            }
+        );
+        // Labels closer together than their height are thinned out
+        assert_eq!(
+            choose_x_label_layout("auto", &long, &space(20.0, 150.0), &measure),
+            XLabelLayout::Rotated {
+                angle: 45.0,
+                step: 2
+            }
+        );
+        // Rotated labels taller than the maximum height are wrapped instead
+        assert_eq!(
+            choose_x_label_layout(
+                "auto",
+                &strings(&["Sprint one", "Sprint two"]),
+                &space(70.0, 50.0),
+                &measure
+            ),
+            XLabelLayout::Wrapped(vec![
+                strings(&["Sprint", "one"]),
+                strings(&["Sprint", "two"])
+            ])
+        );
+
+        assert_eq!(
+            choose_x_label_layout("vertical", &long, &space(40.0, 150.0), &measure),
+            XLabelLayout::Rotated {
+                angle: 90.0,
+                step: 1
+            }
+        );
+        // Unknown strategies warn and behave like auto
+        assert_eq!(
+            choose_x_label_layout("diagonal", &short, &space(50.0, 150.0), &measure),
+            XLabelLayout::Horizontal
+        );
+        assert_eq!(
+            choose_x_label_layout("diagonal", &long, &space(40.0, 150.0), &measure),
+            XLabelLayout::Rotated {
+                angle: 45.0,
+                step: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_rotated_label_step() {
+        assert_eq!(rotated_label_step(45.0, &space(40.0, 150.0)), 1);
+        assert_eq!(rotated_label_step(45.0, &space(20.0, 150.0)), 2);
+        assert_eq!(rotated_label_step(45.0, &space(5.0, 150.0)), 5);
+        assert_eq!(rotated_label_step(90.0, &space(5.0, 150.0)), 4);
+    }
+
+    #[test]
+    fn test_wrap_label() {
+        assert_eq!(
+            wrap_label("Sprint one", 65.0, &measure),
+            vec!["Sprint", "one"]
+        );
+        assert_eq!(
+            wrap_label("Sprint one", 100.0, &measure),
+            vec!["Sprint one"]
+        );
+        // Lines past the maximum are joined onto the last line and truncated
+        assert_eq!(
+            wrap_label("one two three four five", 80.0, &measure),
+            vec!["one two", "three", "four fi…"]
+        );
+        // Words that are too long for a line are truncated
+        assert_eq!(
+            wrap_label("Retrospective notes", 80.0, &measure),
+            vec!["Retrosp…", "notes"]
+        );
+    }
+
+    #[test]
+    fn test_truncate_label() {
+        assert_eq!(truncate_label("abcdef", 60.0, &measure), "abcdef");
+        assert_eq!(truncate_label("abcdef", 40.0, &measure), "abc…");
+        assert_eq!(truncate_label("ab cdef", 40.0, &measure), "ab…");
+        assert_eq!(truncate_label("abcdef", 5.0, &measure), "…");
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use super::{Annotation, AnnotationKind, AxisSide, Curve, Series, SeriesType, XYChart, YAxis};
+use crate::common::writer::{labels_list, quote};
+
+fn axis_keyword(axis: &AxisSide) -> &'static str {
+    match axis {
+        AxisSide::Left => "",
+        AxisSide::Right => " right",
+    }
+}
+
+fn curve_keyword(curve: Curve) -> &'static str {
+    match curve {
+        Curve::Linear => "linear",
+        Curve::Step => "step",
+        Curve::Monotone => "monotone",
+    }
+}
+
+fn format_y_axis(f: &mut fmt::Formatter, axis: &YAxis, side: AxisSide) -> fmt::Result {
+    writeln!(
+        f,
+        "  y-axis{} {} {} --> {}",
+        axis_keyword(&side),
+        quote(&axis.title),
+        axis.min,
+        axis.max
+    )
+}
+
+/// Write attributes such as `{color: #038411, stroke: dashed}`, quoting values that contain
+/// the separators
+fn format_attributes(attributes: &[(&str, Option<String>)]) -> String {
+    let attributes: Vec<String> = attributes
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = value.as_ref()?;
+            if value.trim().is_empty()
+                || value.trim() != value
+                || value.starts_with(['"', '\''])
+                || value.contains([',', '}'])
+            {
+                Some(format!("{}: {}", key, quote(value)))
+            } else {
+                Some(format!("{}: {}", key, value))
+            }
+        })
+        .collect();
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" {{{}}}", attributes.join(", "))
+    }
+}
+
+fn format_series(series: &Series) -> String {
+    let series_type = match series.series_type {
+        SeriesType::Bar => "bar",
+        SeriesType::Line => "line",
+        SeriesType::Area => "area",
+        SeriesType::StackedArea => "stacked-area",
+    };
+    let name = series
+        .name
+        .as_ref()
+        .map(|name| format!(" {}", quote(name)))
+        .unwrap_or_default();
+    let attributes = format_attributes(&[
+        ("color", series.color.clone()),
+        ("points", series.points.clone()),
+        ("stroke", series.stroke.clone()),
+        (
+            "curve",
+            series.curve.map(|curve| curve_keyword(curve).to_string()),
+        ),
+        ("opacity", series.opacity.map(|opacity| opacity.to_string())),
+    ]);
+    let data: Vec<String> = series
+        .data
+        .iter()
+        .map(|value| value.map_or("null".to_string(), |value| value.to_string()))
+        .collect();
+    format!(
+        "{}{}{}{} [{}]",
+        series_type,
+        axis_keyword(&series.axis),
+        name,
+        attributes,
+        data.join(", ")
+    )
+}
+
+fn format_annotation(annotation: &Annotation) -> String {
+    let statement = match &annotation.kind {
+        AnnotationKind::HorizontalLine { value } => {
+            format!("hline{} {}", axis_keyword(&annotation.axis), value)
+        }
+        // The category is always quoted so it can't be mistaken for an axis
+        AnnotationKind::VerticalLine { category } => {
+            format!(
+                "vline{} {}",
+                axis_keyword(&annotation.axis),
+                quote(category)
+            )
+        }
+        AnnotationKind::Band { from, to } => {
+            format!("band{} {} --> {}", axis_keyword(&annotation.axis), from, to)
+        }
+    };
+    let label = annotation
+        .label
+        .as_ref()
+        .map(|label| format!(" {}", quote(label)))
+        .unwrap_or_default();
+    let attributes = format_attributes(&[
+        ("color", annotation.color.clone()),
+        ("stroke", annotation.stroke.clone()),
+        (
+            "opacity",
+            annotation.opacity.map(|opacity| opacity.to_string()),
+        ),
+    ]);
+    format!("{}{}{}", statement, label, attributes)
+}
+
+impl XYChart {
+    /// The chart in mermaid syntax, preceded by the `%%{init}%%` line of any config
+    pub fn to_mermaid(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Write the chart in the syntax accepted by `parse_xychart`
+impl fmt::Display for XYChart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(config) = &self.config {
+            writeln!(f, "{}", config)?;
+        }
+
+        writeln!(f, "xychart-beta")?;
+        if let Some(title) = &self.title {
+            writeln!(f, "  title {}", quote(title))?;
+        }
+        if let Some(legend) = &self.legend {
+            writeln!(f, "  legend {}", labels_list(legend))?;
+        }
+        writeln!(f, "  x-axis {}", labels_list(&self.x_axis.labels))?;
+        format_y_axis(f, &self.y_axis, AxisSide::Left)?;
+        if let Some(right) = &self.y_axis_right {
+            format_y_axis(f, right, AxisSide::Right)?;
+        }
+        for series in &self.series {
+            writeln!(f, "  {}", format_series(series))?;
+        }
+        for annotation in &self.annotations {
+            writeln!(f, "  {}", format_annotation(annotation))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::writer::strategies::{chart_config, quotable_text};
+    use crate::xychart::{
+        parse_xychart, parse_xychart_content, Annotation, AnnotationKind, AxisSide, Curve, Series,
+        SeriesType, XAxis, XYChart, YAxis,
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_write_xychart() {
+        let input = r##"xychart-beta
+  title "Story points vs #quot;cycle time#quot;"
+  legend [Points, "Cycle time, days"]
+  x-axis [Sprint 1, "Sprint 2, late", Say "hi", "#quot;Quoted#quot;"]
+  y-axis "Story points" -10 --> 60
+  y-axis right "Cycle time (days)" 0 --> 10.5
+  bar [40, null, 48]
+  line right "Cycle time" {color: #ff0000, stroke: dashed, curve: monotone} [4.5, 3, 3.5]
+  stacked-area {opacity: 0.3} [1, 2, 3]
+  hline 50 "Target" {color: "#aaa, #bbb"}
+  vline right "Sprint 2, late" {color: ""}
+  band right 2 --> 4 {opacity: 0.1}
+"##;
+
+        let (remaining, xychart) = parse_xychart_content(input, None).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(xychart.to_mermaid(), input);
+    }
+
+    /// Labels in a list are trimmed unless they are quoted
+    fn label() -> impl Strategy<Value = String> {
+        quotable_text()
+    }
+
+    fn value() -> impl Strategy<Value = f64> {
+        -1e6f64..1e6
+    }
+
+    fn side() -> impl Strategy<Value = AxisSide> {
+        prop_oneof![Just(AxisSide::Left), Just(AxisSide::Right)]
+    }
+
+    fn y_axis() -> impl Strategy<Value = YAxis> {
+        (quotable_text(), value(), value()).prop_map(|(title, min, max)| YAxis { title, min, max })
+    }
+
+    /// A colour, which can be any text
+    fn attribute() -> impl Strategy<Value = Option<String>> {
+        prop::option::of(quotable_text())
+    }
+
+    fn one_of(values: &'static [&'static str]) -> impl Strategy<Value = Option<String>> {
+        prop::option::of(prop::sample::select(values).prop_map(str::to_string))
+    }
+
+    fn curve() -> impl Strategy<Value = Option<Curve>> {
+        prop::option::of(prop_oneof![
+            Just(Curve::Linear),
+            Just(Curve::Step),
+            Just(Curve::Monotone)
+        ])
+    }
+
+    fn series() -> impl Strategy<Value = Series> {
+        (
+            prop_oneof![
+                Just(SeriesType::Bar),
+                Just(SeriesType::Line),
+                Just(SeriesType::Area),
+                Just(SeriesType::StackedArea),
+            ],
+            side(),
+            prop::option::of(quotable_text()),
+            (
+                attribute(),
+                one_of(&["none", "square", "diamond"]),
+                one_of(&["solid", "dashed"]),
+                curve(),
+            ),
+            prop::option::of(0f64..1.0),
+            prop::collection::vec(prop::option::of(value()), 0..6),
+        )
+            .prop_map(
+                |(series_type, axis, name, (color, points, stroke, curve), opacity, data)| Series {
+                    series_type,
+                    axis,
+                    name,
+                    color,
+                    points,
+                    stroke,
+                    curve,
+                    opacity,
+                    data,
+                },
+            )
+    }
+
+    fn annotation() -> impl Strategy<Value = Annotation> {
+        (
+            prop_oneof![
+                value().prop_map(|value| AnnotationKind::HorizontalLine { value }),
+                quotable_text().prop_map(|category| AnnotationKind::VerticalLine { category }),
+                (value(), value()).prop_map(|(from, to)| AnnotationKind::Band { from, to }),
+            ],
+            side(),
+            prop::option::of(quotable_text()),
+            (
+                attribute(),
+                one_of(&["solid", "dashed"]),
+                prop::option::of(0f64..1.0),
+            ),
+        )
+            .prop_map(|(kind, axis, label, (color, stroke, opacity))| Annotation {
+                kind,
+                axis,
+                label,
+                color,
+                stroke,
+                opacity,
+            })
+    }
+
+    fn xychart() -> impl Strategy<Value = XYChart> {
+        (
+            prop::option::of(chart_config()),
+            prop::option::of(quotable_text()),
+            prop::option::of(prop::collection::vec(label(), 0..4)),
+            prop::collection::vec(label(), 0..6),
+            (y_axis(), prop::option::of(y_axis())),
+            prop::collection::vec(series(), 0..4),
+            prop::collection::vec(annotation(), 0..4),
+        )
+            .prop_map(
+                |(config, title, legend, labels, (y_axis, y_axis_right), series, annotations)| {
+                    XYChart {
+                        config,
+                        title,
+                        legend,
+                        x_axis: XAxis { labels },
+                        y_axis,
+                        y_axis_right,
+                        series,
+                        annotations,
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_xychart_round_trip(xychart in xychart()) {
+            let written = xychart.to_mermaid();
+            let (remaining, parsed) = parse_xychart(&written).unwrap();
+            prop_assert_eq!(remaining, "");
+            prop_assert_eq!(parsed, xychart);
+        }
+    }
+}